@@ -1,41 +1,140 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::PathBuf;
+use std::rc::Rc;
+
 use ratatui::widgets::ListState;
 use tokio::sync::mpsc;
 
-use crate::brew::types::{CaskInfo, FormulaInfo, PackageKind, RemoteData};
+use crate::brew::types::{
+    CaskInfo, CatalogSource, FormulaInfo, InstalledCask, InstalledFormula, InstalledPackages, OutdatedPackage,
+    PackageCounts, PackageKind, RemoteData, ServiceInfo, TapInfo,
+};
+use crate::brewfile::{self, Brewfile, Entry, EntryKind, ExportOptions, Plan, RestartService};
+use crate::config::Config;
+use crate::deps::{DepGraph, DepTree, ReverseIndex};
+use crate::jobs::{JobId, JobQueue, JobStatus};
+use crate::license::{self, LicenseGroup, ReportFormat};
+use crate::manifest::{self, Check, Manifest, Outcome, Rule};
+use crate::policy::Violation;
+use crate::query::{Filterable, Query};
+use crate::search::{rank, FuzzyMatch, SearchScope, Searchable};
+use crate::sort::{self, SortKey, Sortable, Version};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Tab {
     InstalledFormulae,
     InstalledCasks,
+    Outdated,
+    Leaves,
     BrowseFormulae,
     BrowseCasks,
+    Taps,
+    Services,
+    Compliance,
 }
 
 impl Tab {
     pub fn next(self) -> Self {
         match self {
             Tab::InstalledFormulae => Tab::InstalledCasks,
-            Tab::InstalledCasks => Tab::BrowseFormulae,
+            Tab::InstalledCasks => Tab::Outdated,
+            Tab::Outdated => Tab::Leaves,
+            Tab::Leaves => Tab::BrowseFormulae,
             Tab::BrowseFormulae => Tab::BrowseCasks,
-            Tab::BrowseCasks => Tab::InstalledFormulae,
+            Tab::BrowseCasks => Tab::Taps,
+            Tab::Taps => Tab::Services,
+            Tab::Services => Tab::Compliance,
+            Tab::Compliance => Tab::InstalledFormulae,
         }
     }
 
     pub fn prev(self) -> Self {
         match self {
-            Tab::InstalledFormulae => Tab::BrowseCasks,
+            Tab::InstalledFormulae => Tab::Compliance,
             Tab::InstalledCasks => Tab::InstalledFormulae,
-            Tab::BrowseFormulae => Tab::InstalledCasks,
+            Tab::Outdated => Tab::InstalledCasks,
+            Tab::Leaves => Tab::Outdated,
+            Tab::BrowseFormulae => Tab::Leaves,
             Tab::BrowseCasks => Tab::BrowseFormulae,
+            Tab::Taps => Tab::BrowseCasks,
+            Tab::Services => Tab::Taps,
+            Tab::Compliance => Tab::Services,
+        }
+    }
+
+    /// Whether `o`/`O` reorder the list. Taps, services and checks have no
+    /// package fields to sort on.
+    pub fn is_sortable(self) -> bool {
+        !matches!(self, Tab::Taps | Tab::Services | Tab::Compliance)
+    }
+}
+
+/// A row of the package list: the name plus the characters that matched the
+/// search query, for highlighting.
+#[derive(Debug, Clone)]
+pub struct VisibleItem {
+    pub name: String,
+    pub positions: Vec<usize>,
+}
+
+/// What a list of `VisibleItem`s was built for. Changing any of it rebuilds
+/// the list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ListView {
+    tab: Tab,
+    search: String,
+    scope: SearchScope,
+    sort_key: SortKey,
+    descending: bool,
+}
+
+fn to_visible<T: Searchable>(matches: Vec<(&T, FuzzyMatch)>) -> Vec<VisibleItem> {
+    matches
+        .into_iter()
+        .map(|(item, m)| VisibleItem {
+            name: item.search_name().to_string(),
+            positions: m.positions,
+        })
+        .collect()
+}
+
+/// A `brew services` subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceCommand {
+    Start,
+    Stop,
+    Restart,
+    /// Run without registering it to start at login/boot.
+    Run,
+}
+
+impl ServiceCommand {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ServiceCommand::Start => "start",
+            ServiceCommand::Stop => "stop",
+            ServiceCommand::Restart => "restart",
+            ServiceCommand::Run => "run",
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub enum BrewAction {
-    Install { name: String, kind: PackageKind },
-    Uninstall { name: String, kind: PackageKind },
-    Upgrade { name: String, kind: PackageKind },
+    /// `options` are extra install flags, e.g. from a Brewfile's `args:`.
+    Install { names: Vec<String>, kind: PackageKind, options: Vec<String> },
+    Uninstall { names: Vec<String>, kind: PackageKind, ignore_dependencies: bool },
+    Upgrade { names: Vec<String>, kind: PackageKind },
+    Pin { names: Vec<String> },
+    Unpin { names: Vec<String> },
+    /// `names` is the tap, optionally followed by its remote URL.
+    Tap { names: Vec<String> },
+    Untap { names: Vec<String> },
+    Service { command: ServiceCommand, names: Vec<String> },
+    /// `brew autoremove`; `preview` is what we expect it to remove.
+    Autoremove { preview: Vec<String> },
 }
 
 impl BrewAction {
@@ -44,14 +143,26 @@ impl BrewAction {
             BrewAction::Install { .. } => "install",
             BrewAction::Uninstall { .. } => "uninstall",
             BrewAction::Upgrade { .. } => "upgrade",
+            BrewAction::Pin { .. } => "pin",
+            BrewAction::Unpin { .. } => "unpin",
+            BrewAction::Tap { .. } => "tap",
+            BrewAction::Untap { .. } => "untap",
+            BrewAction::Service { .. } => "services",
+            BrewAction::Autoremove { .. } => "autoremove",
         }
     }
 
-    pub fn name(&self) -> &str {
+    pub fn names(&self) -> &[String] {
         match self {
-            BrewAction::Install { name, .. } => name,
-            BrewAction::Uninstall { name, .. } => name,
-            BrewAction::Upgrade { name, .. } => name,
+            BrewAction::Install { names, .. } => names,
+            BrewAction::Uninstall { names, .. } => names,
+            BrewAction::Upgrade { names, .. } => names,
+            BrewAction::Pin { names } => names,
+            BrewAction::Unpin { names } => names,
+            BrewAction::Tap { names } => names,
+            BrewAction::Untap { names } => names,
+            BrewAction::Service { names, .. } => names,
+            BrewAction::Autoremove { preview } => preview,
         }
     }
 
@@ -60,6 +171,64 @@ impl BrewAction {
             BrewAction::Install { kind, .. } => *kind,
             BrewAction::Uninstall { kind, .. } => *kind,
             BrewAction::Upgrade { kind, .. } => *kind,
+            BrewAction::Pin { .. } | BrewAction::Unpin { .. } => PackageKind::Formula,
+            BrewAction::Tap { .. } | BrewAction::Untap { .. } => PackageKind::Formula,
+            BrewAction::Service { .. } => PackageKind::Formula,
+            BrewAction::Autoremove { .. } => PackageKind::Formula,
+        }
+    }
+
+    /// The arguments to pass to `brew`.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec![self.verb().to_string()];
+        if let BrewAction::Service { command, .. } = self {
+            args.push(command.as_str().to_string());
+        }
+        if self.kind() == PackageKind::Cask {
+            args.push("--cask".to_string());
+        }
+        if let BrewAction::Install { options, .. } = self {
+            args.extend(options.iter().cloned());
+        }
+        if let BrewAction::Uninstall { ignore_dependencies: true, .. } = self {
+            args.push("--ignore-dependencies".to_string());
+        }
+        // autoremove decides for itself what to remove.
+        if !matches!(self, BrewAction::Autoremove { .. }) {
+            args.extend(self.names().iter().cloned());
+        }
+        args
+    }
+}
+
+/// Installed formulae that still need a package the user is about to
+/// uninstall.
+#[derive(Debug, Clone)]
+pub struct Dependents {
+    pub name: String,
+    pub used_by: Vec<String>,
+}
+
+/// What a line of text typed in `Mode::Input` is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputPurpose {
+    /// A tap to add: `user/repo`, optionally followed by a URL.
+    Tap,
+    /// Where to write a Brewfile, optionally followed by export flags.
+    Export,
+    /// A Brewfile to diff against the installed state.
+    Import,
+    /// Where to write the license report; the extension picks the format.
+    LicenseReport,
+}
+
+impl InputPurpose {
+    pub fn prompt(self) -> &'static str {
+        match self {
+            InputPurpose::Tap => "Tap (user/repo [URL])",
+            InputPurpose::Export => "Export Brewfile (PATH [--describe] [--versions] [--force])",
+            InputPurpose::Import => "Load Brewfile (PATH)",
+            InputPurpose::LicenseReport => "Export license report (PATH ending in .json, .csv or .md)",
         }
     }
 }
@@ -68,89 +237,337 @@ impl BrewAction {
 pub enum Mode {
     Normal,
     Search,
-    Confirm { action: BrewAction },
-    Running { action: BrewAction, output: Vec<String>, done: bool },
+    /// Reading a line of text into `App::input`.
+    Input(InputPurpose),
+    /// Waiting for the user to confirm `actions`. Uninstalls with
+    /// `dependents` need an explicit override; `skipped` lists pinned
+    /// formulae left out of an upgrade, and `denied` what the policy
+    /// refused to install.
+    Confirm { actions: Vec<BrewAction>, dependents: Vec<Dependents>, skipped: Vec<String>, denied: Vec<Violation> },
+    Jobs,
+    DepTree,
+    /// The plan for the loaded `App::brewfile`.
+    Brewfile,
+    /// Installed formulae grouped by license.
+    Licenses,
 }
 
 pub enum AppEvent {
     Key(crossterm::event::KeyEvent),
-    InstalledLoaded { formulae: Vec<String>, casks: Vec<String> },
-    BrowseFormulaeLoaded(Vec<FormulaInfo>),
-    BrowseCasksLoaded(Vec<CaskInfo>),
-    ActionOutput(String),
-    ActionDone(bool),
-    Error(String),
+    InstalledLoaded { formulae: Vec<InstalledFormula>, casks: Vec<InstalledCask> },
+    BrowseFormulaeLoaded { data: Vec<FormulaInfo>, source: CatalogSource },
+    BrowseCasksLoaded { data: Vec<CaskInfo>, source: CatalogSource },
+    BrowseFailed { kind: PackageKind, error: String },
+    /// Formulae from third-party taps, merged into the browse catalog.
+    TapFormulaeLoaded(Vec<FormulaInfo>),
+    TapCasksLoaded(Vec<CaskInfo>),
+    TapCatalogFailed { kind: PackageKind, error: String },
+    OutdatedLoaded(Vec<OutdatedPackage>),
+    OutdatedFailed(String),
+    TapsLoaded(Vec<TapInfo>),
+    TapsFailed(String),
+    ServicesLoaded(Vec<ServiceInfo>),
+    ServicesFailed(String),
+    BrewfileExported { path: PathBuf, entries: usize },
+    BrewfileExportFailed(String),
+    DiskUsageLoaded(PackageCounts),
+    DiskUsageFailed(String),
+    PopularityLoaded(PackageCounts),
+    PopularityFailed(String),
+    ActionOutput { job: JobId, line: String },
+    ActionDone { job: JobId, exit_code: Option<i32> },
+    ActionCancelled { job: JobId },
     DebugLog(String),
 }
 
 pub struct App {
     pub tab: Tab,
     pub mode: Mode,
-    pub installed_formulae: Vec<String>,
-    pub installed_casks: Vec<String>,
+    pub installed_formulae: Vec<InstalledFormula>,
+    pub installed_casks: Vec<InstalledCask>,
+    pub installed_loaded: bool,
     pub browse_formulae: RemoteData<Vec<FormulaInfo>>,
     pub browse_casks: RemoteData<Vec<CaskInfo>>,
+    pub formulae_source: Option<CatalogSource>,
+    pub casks_source: Option<CatalogSource>,
+    /// A failed half of a browse catalog that still loaded in part: the API
+    /// catalog when only tapped packages are listed, or the taps.
+    pub formulae_error: Option<String>,
+    pub casks_error: Option<String>,
+    pub outdated: RemoteData<Vec<OutdatedPackage>>,
+    pub taps: RemoteData<Vec<TapInfo>>,
+    pub services: RemoteData<Vec<ServiceInfo>>,
+    /// Built from the installed packages and catalogs whenever one loads.
+    pub reverse_index: ReverseIndex,
     pub list_state: ListState,
+    /// The rows of the current tab, built by `visible_items` on first use
+    /// and dropped whenever data arrives.
+    visible: RefCell<Option<(ListView, Rc<[VisibleItem]>)>>,
+    /// Packages marked for a batch action in the current tab, keyed by kind
+    /// since the Outdated tab can list a formula and a cask of the same name.
+    pub marked: HashSet<(PackageKind, String)>,
     pub search: String,
+    pub search_scope: SearchScope,
+    /// Text typed in `Mode::Input`.
+    pub input: String,
+    pub sort_key: SortKey,
+    pub sort_descending: bool,
+    /// Bytes on disk per installed package, loaded when sorting by size.
+    pub disk_usage: RemoteData<PackageCounts>,
+    /// 30-day install counts, loaded when sorting by popularity.
+    pub popularity: RemoteData<PackageCounts>,
+    pub jobs: JobQueue,
+    pub jobs_state: ListState,
+    pub dep_tree: DepTree,
+    pub brewfile: Option<Brewfile>,
+    pub manifest: Option<Manifest>,
+    /// Every manifest rule checked against the installed state, or nothing
+    /// without a manifest.
+    pub compliance: Vec<Check>,
+    /// First visible line of the Brewfile plan.
+    pub brewfile_scroll: u16,
+    /// First visible line of the license audit.
+    pub licenses_scroll: u16,
+    /// A one-off message for the help bar, cleared by the next key.
+    pub notice: Option<String>,
     pub should_quit: bool,
+    /// Quit was requested while a job ran; waiting for it to stop.
+    quitting: bool,
     pub event_tx: mpsc::Sender<AppEvent>,
+    pub config: Config,
     pub debug_mode: bool,
     pub show_debug: bool,
     pub debug_logs: Vec<String>,
 }
 
 impl App {
-    pub fn new(event_tx: mpsc::Sender<AppEvent>, debug_mode: bool) -> Self {
+    pub fn new(event_tx: mpsc::Sender<AppEvent>, config: Config) -> Self {
         Self {
             tab: Tab::InstalledFormulae,
             mode: Mode::Normal,
             installed_formulae: vec![],
             installed_casks: vec![],
+            installed_loaded: false,
             browse_formulae: RemoteData::NotLoaded,
             browse_casks: RemoteData::NotLoaded,
+            formulae_source: None,
+            casks_source: None,
+            formulae_error: None,
+            casks_error: None,
+            outdated: RemoteData::NotLoaded,
+            taps: RemoteData::NotLoaded,
+            services: RemoteData::NotLoaded,
+            reverse_index: ReverseIndex::default(),
             list_state: ListState::default(),
+            visible: RefCell::new(None),
+            marked: HashSet::new(),
             search: String::new(),
+            search_scope: SearchScope::default(),
+            input: String::new(),
+            sort_key: SortKey::default(),
+            sort_descending: false,
+            disk_usage: RemoteData::NotLoaded,
+            popularity: RemoteData::NotLoaded,
+            jobs: JobQueue::default(),
+            jobs_state: ListState::default(),
+            dep_tree: DepTree::default(),
+            brewfile: None,
+            manifest: None,
+            compliance: vec![],
+            brewfile_scroll: 0,
+            licenses_scroll: 0,
+            notice: None,
             should_quit: false,
+            quitting: false,
             event_tx,
-            debug_mode,
+            debug_mode: config.debug,
+            config,
             show_debug: false,
             debug_logs: vec![],
         }
     }
 
-    /// Returns the filtered list of names visible in the current tab.
-    pub fn visible_items(&self) -> Vec<String> {
-        let query = self.search.to_lowercase();
+    /// Parses the search box as a query. See `query::parse` for the syntax.
+    pub fn parsed_query(&self) -> Result<Query, String> {
+        crate::query::parse(&self.search)
+    }
+
+    /// Returns the entries visible in the current tab, filtered by the query's
+    /// field filters and ranked by its free text. The list is cached until
+    /// the tab, query or sort order changes or new data arrives.
+    pub fn visible_items(&self) -> Rc<[VisibleItem]> {
+        let view = ListView {
+            tab: self.tab,
+            search: self.search.clone(),
+            scope: self.search_scope,
+            sort_key: self.sort_key,
+            descending: self.sort_descending,
+        };
+        if let Some((cached, items)) = &*self.visible.borrow() {
+            if *cached == view {
+                return items.clone();
+            }
+        }
+        let items: Rc<[VisibleItem]> = self.build_visible_items().into();
+        *self.visible.borrow_mut() = Some((view, items.clone()));
+        items
+    }
+
+    /// Drops the cached `visible_items` after the data they come from changed.
+    fn invalidate_visible(&mut self) {
+        *self.visible.get_mut() = None;
+    }
+
+    fn build_visible_items(&self) -> Vec<VisibleItem> {
+        let Ok(q) = self.parsed_query() else {
+            return vec![];
+        };
+        let (text, scope) = (q.text.as_str(), self.search_scope);
         match self.tab {
-            Tab::InstalledFormulae => self
-                .installed_formulae
-                .iter()
-                .filter(|n| n.to_lowercase().contains(&query))
-                .cloned()
-                .collect(),
-            Tab::InstalledCasks => self
-                .installed_casks
-                .iter()
-                .filter(|n| n.to_lowercase().contains(&query))
-                .cloned()
-                .collect(),
-            Tab::BrowseFormulae => match &self.browse_formulae {
-                RemoteData::Loaded(v) => v
+            Tab::InstalledFormulae => {
+                let items = self.installed_formulae.iter().filter(|f| q.matches(*f, true));
+                to_visible(self.sorted(rank(items, text, scope)))
+            }
+            Tab::InstalledCasks => {
+                let items = self.installed_casks.iter().filter(|c| q.matches(*c, true));
+                to_visible(self.sorted(rank(items, text, scope)))
+            }
+            Tab::Outdated => to_visible(self.ranked_outdated()),
+            Tab::Leaves => {
+                let index = &self.reverse_index;
+                let items = self
+                    .installed_formulae
                     .iter()
-                    .filter(|f| f.name.to_lowercase().contains(&query))
-                    .map(|f| f.name.clone())
-                    .collect(),
+                    .filter(|f| index.used_by_installed(PackageKind::Formula, &f.name).is_empty() && q.matches(*f, true));
+                to_visible(self.sorted(rank(items, text, scope)))
+            }
+            Tab::BrowseFormulae => match &self.browse_formulae {
+                RemoteData::Loaded(v) => {
+                    let installed: HashSet<&str> = self.installed_formulae.iter().map(|f| f.name.as_str()).collect();
+                    let items = v
+                        .iter()
+                        .filter(|f| q.matches(*f, installed.contains(f.name.as_str())));
+                    to_visible(self.sorted(rank(items, text, scope)))
+                }
                 _ => vec![],
             },
             Tab::BrowseCasks => match &self.browse_casks {
-                RemoteData::Loaded(v) => v
-                    .iter()
-                    .filter(|c| c.token.to_lowercase().contains(&query))
-                    .map(|c| c.token.clone())
-                    .collect(),
+                RemoteData::Loaded(v) => {
+                    let installed: HashSet<&str> = self.installed_casks.iter().map(|c| c.token.as_str()).collect();
+                    let items = v
+                        .iter()
+                        .filter(|c| q.matches(*c, installed.contains(c.token.as_str())));
+                    to_visible(self.sorted(rank(items, text, scope)))
+                }
+                _ => vec![],
+            },
+            // Taps have no package fields to filter or sort on.
+            Tab::Taps => match &self.taps {
+                RemoteData::Loaded(v) => to_visible(rank(v, text, scope)),
                 _ => vec![],
             },
+            Tab::Services => match &self.services {
+                RemoteData::Loaded(v) => to_visible(rank(v, text, scope)),
+                _ => vec![],
+            },
+            Tab::Compliance => to_visible(rank(&self.compliance, text, scope)),
+        }
+    }
+
+    pub fn set_manifest(&mut self, manifest: Option<Manifest>) {
+        self.manifest = manifest;
+        self.refresh_compliance();
+    }
+
+    /// Re-checks every manifest rule against the installed state. Called
+    /// whenever the manifest, installed packages or taps change.
+    fn refresh_compliance(&mut self) {
+        self.invalidate_visible();
+        let Some(manifest) = &self.manifest else {
+            self.compliance = vec![];
+            return;
+        };
+        let installed = self
+            .installed_loaded
+            .then_some((self.installed_formulae.as_slice(), self.installed_casks.as_slice()));
+        let taps = match &self.taps {
+            RemoteData::Loaded(v) => Some(v.as_slice()),
+            _ => None,
+        };
+        self.compliance = manifest::check(manifest, installed, taps);
+    }
+
+    /// Returns the filtered outdated entries, in the same order as
+    /// `visible_items` for the Outdated tab.
+    pub fn visible_outdated(&self) -> Vec<&OutdatedPackage> {
+        self.ranked_outdated().into_iter().map(|(p, _)| p).collect()
+    }
+
+    /// Outdated entries carry no metadata of their own, so field filters are
+    /// evaluated against the matching installed package.
+    fn ranked_outdated(&self) -> Vec<(&OutdatedPackage, FuzzyMatch)> {
+        let (Ok(q), RemoteData::Loaded(v)) = (self.parsed_query(), &self.outdated) else {
+            return vec![];
+        };
+        let items = v.iter().filter(|p| {
+            if q.filters.is_empty() {
+                return true;
+            }
+            let installed: Option<&dyn Filterable> = match p.kind {
+                PackageKind::Formula => self
+                    .installed_formulae
+                    .iter()
+                    .find(|f| f.name == p.name)
+                    .map(|f| f as &dyn Filterable),
+                PackageKind::Cask => self
+                    .installed_casks
+                    .iter()
+                    .find(|c| c.token == p.name)
+                    .map(|c| c as &dyn Filterable),
+            };
+            installed.is_some_and(|i| q.matches(i, true))
+        });
+        self.sorted(rank(items, &q.text, self.search_scope))
+    }
+
+    /// Reorders ranked matches by the current sort key. `SortKey::Default`
+    /// keeps the ranking (or catalog order when not searching).
+    fn sorted<'a, T: Sortable>(&self, mut matches: Vec<(&'a T, FuzzyMatch)>) -> Vec<(&'a T, FuzzyMatch)> {
+        let desc = self.sort_descending;
+        match self.sort_key {
+            SortKey::Default => {}
+            SortKey::Name => sort::sort_by_key(&mut matches, desc, |(p, _)| Some(p.search_name().to_lowercase())),
+            SortKey::InstallDate => {
+                let times = self.install_times();
+                sort::sort_by_key(&mut matches, desc, |(p, _)| times.get(&(p.kind(), p.search_name())).copied());
+            }
+            SortKey::Size => {
+                if let RemoteData::Loaded(sizes) = &self.disk_usage {
+                    sort::sort_by_key(&mut matches, desc, |(p, _)| sizes.get(p.kind(), p.search_name()));
+                }
+            }
+            SortKey::Popularity => {
+                if let RemoteData::Loaded(counts) = &self.popularity {
+                    sort::sort_by_key(&mut matches, desc, |(p, _)| counts.get(p.kind(), p.search_name()));
+                }
+            }
+            SortKey::Version => sort::sort_by_key(&mut matches, desc, |(p, _)| p.version().map(Version)),
         }
+        matches
+    }
+
+    /// Install times of everything installed, so browse and outdated entries
+    /// can be sorted by them too.
+    fn install_times(&self) -> HashMap<(PackageKind, &str), i64> {
+        let formulae = self
+            .installed_formulae
+            .iter()
+            .filter_map(|f| Some(((PackageKind::Formula, f.name.as_str()), f.install_time()?)));
+        let casks = self
+            .installed_casks
+            .iter()
+            .filter_map(|c| Some(((PackageKind::Cask, c.token.as_str()), c.installed_time?)));
+        formulae.chain(casks).collect()
     }
 
     pub fn select_next(&mut self) {
@@ -180,9 +597,79 @@ impl App {
         }
     }
 
+    /// Re-sorts after sort data for `key` arrives, keeping `selected` (the
+    /// item selected before the data changed) selected. Lists sorted by
+    /// anything else don't move.
+    fn resort_list(&mut self, key: SortKey, selected: Option<String>) {
+        if self.sort_key == key && self.tab.is_sortable() {
+            self.reselect(selected);
+        }
+    }
+
+    /// Selects `name` again after the list changed under it, or the first
+    /// item if it's gone.
+    fn reselect(&mut self, name: Option<String>) {
+        let items = self.visible_items();
+        let i = name.and_then(|name| items.iter().position(|item| item.name == name));
+        self.list_state.select(i.or(if items.is_empty() { None } else { Some(0) }));
+    }
+
     pub fn selected_name(&self) -> Option<String> {
         let items = self.visible_items();
-        self.list_state.selected().and_then(|i| items.get(i).cloned())
+        self.list_state.selected().and_then(|i| items.get(i).map(|item| item.name.clone()))
+    }
+
+    /// The kind of package listed on the current tab. The Outdated tab mixes
+    /// both, so its rows carry their own kind instead.
+    pub fn tab_kind(&self) -> PackageKind {
+        match self.tab {
+            Tab::InstalledCasks | Tab::BrowseCasks => PackageKind::Cask,
+            _ => PackageKind::Formula,
+        }
+    }
+
+    /// The mark keys of the visible rows, in list order.
+    fn visible_keys(&self) -> Vec<(PackageKind, String)> {
+        if self.tab == Tab::Outdated {
+            return self.visible_outdated().into_iter().map(|p| (p.kind, p.name.clone())).collect();
+        }
+        let kind = self.tab_kind();
+        self.visible_items().iter().map(|item| (kind, item.name.clone())).collect()
+    }
+
+    pub fn is_marked(&self, kind: PackageKind, name: &str) -> bool {
+        self.marked.contains(&(kind, name.to_string()))
+    }
+
+    /// The names an action should apply to: every marked entry if any are
+    /// marked, otherwise just the selected one.
+    pub fn action_targets(&self) -> Vec<String> {
+        if self.marked.is_empty() {
+            return self.selected_name().into_iter().collect();
+        }
+        let mut names: Vec<String> = self.marked.iter().map(|(_, name)| name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    fn toggle_mark(&mut self) {
+        let Some(i) = self.list_state.selected() else {
+            return;
+        };
+        if let Some(key) = self.visible_keys().into_iter().nth(i) {
+            if !self.marked.remove(&key) {
+                self.marked.insert(key);
+            }
+            self.select_next();
+        }
+    }
+
+    fn invert_marks(&mut self) {
+        for key in self.visible_keys() {
+            if !self.marked.remove(&key) {
+                self.marked.insert(key);
+            }
+        }
     }
 
     pub fn selected_formula_info(&self) -> Option<&FormulaInfo> {
@@ -201,28 +688,116 @@ impl App {
         }
     }
 
+    pub fn selected_tap(&self) -> Option<&TapInfo> {
+        let name = self.selected_name()?;
+        match &self.taps {
+            RemoteData::Loaded(v) => v.iter().find(|t| t.name == name),
+            _ => None,
+        }
+    }
+
+    pub fn selected_check(&self) -> Option<Check> {
+        let name = self.selected_name()?;
+        self.compliance.iter().find(|c| c.label == name).cloned()
+    }
+
+    pub fn selected_service(&self) -> Option<&ServiceInfo> {
+        let name = self.selected_name()?;
+        match &self.services {
+            RemoteData::Loaded(v) => v.iter().find(|s| s.name == name),
+            _ => None,
+        }
+    }
+
+    pub fn selected_installed_formula(&self) -> Option<&InstalledFormula> {
+        let name = self.selected_name()?;
+        self.installed_formulae.iter().find(|f| f.name == name)
+    }
+
+    pub fn selected_installed_cask(&self) -> Option<&InstalledCask> {
+        let name = self.selected_name()?;
+        self.installed_casks.iter().find(|c| c.token == name)
+    }
+
+    /// The name of the selected formula on any tab that lists formulae.
+    pub fn selected_formula_name(&self) -> Option<String> {
+        match self.tab {
+            Tab::InstalledFormulae | Tab::Leaves | Tab::BrowseFormulae => self.selected_name(),
+            Tab::Outdated => self
+                .selected_outdated()
+                .filter(|p| p.kind == PackageKind::Formula)
+                .map(|p| p.name.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn dep_graph(&self) -> DepGraph<'_> {
+        let catalog = match &self.browse_formulae {
+            RemoteData::Loaded(v) => Some(v.as_slice()),
+            _ => None,
+        };
+        DepGraph::new(catalog, &self.installed_formulae)
+    }
+
+    fn rebuild_reverse_index(&mut self) {
+        let formulae = match &self.browse_formulae {
+            RemoteData::Loaded(v) => Some(v.as_slice()),
+            _ => None,
+        };
+        let casks = match &self.browse_casks {
+            RemoteData::Loaded(v) => Some(v.as_slice()),
+            _ => None,
+        };
+        self.reverse_index = ReverseIndex::new(formulae, casks, &self.installed_formulae, &self.installed_casks);
+    }
+
+    pub fn is_formula_installed(&self, name: &str) -> bool {
+        self.installed_formulae.iter().any(|f| f.name == name)
+    }
+
+    pub fn is_formula_pinned(&self, name: &str) -> bool {
+        self.installed_formulae.iter().any(|f| f.name == name && f.pinned)
+    }
+
+    pub fn is_cask_installed(&self, token: &str) -> bool {
+        self.installed_casks.iter().any(|c| c.token == token)
+    }
+
+    /// Outdated entries can share a name between a formula and a cask, so
+    /// look them up by position rather than by name.
+    pub fn selected_outdated(&self) -> Option<&OutdatedPackage> {
+        let i = self.list_state.selected()?;
+        self.visible_outdated().get(i).copied()
+    }
+
     pub fn handle(&mut self, event: AppEvent) {
         use crossterm::event::{KeyCode, KeyModifiers};
 
+        // Everything but key presses and log lines may change what's listed.
+        if !matches!(event, AppEvent::Key(_) | AppEvent::ActionOutput { .. } | AppEvent::DebugLog(_)) {
+            self.invalidate_visible();
+        }
+
         match event {
             AppEvent::Key(key) => {
                 // Ctrl-C always quits
                 if key.modifiers == KeyModifiers::CONTROL && key.code == KeyCode::Char('c') {
-                    self.should_quit = true;
+                    self.quit();
                     return;
                 }
 
                 match &self.mode {
                     Mode::Normal => self.handle_normal_key(key),
                     Mode::Search => self.handle_search_key(key),
-                    Mode::Confirm { .. } => self.handle_confirm_key(key),
-                    Mode::Running { done, .. } => {
-                        if *done {
-                            if key.code == KeyCode::Esc || key.code == KeyCode::Enter || key.code == KeyCode::Char('q') {
-                                self.mode = Mode::Normal;
-                            }
-                        }
+                    Mode::Input(purpose) => {
+                        let purpose = *purpose;
+                        self.handle_input_key(key, purpose);
                     }
+                    Mode::Confirm { .. } => self.handle_confirm_key(key),
+                    Mode::Jobs => self.handle_jobs_key(key),
+                    Mode::DepTree => self.handle_dep_tree_key(key),
+                    Mode::Brewfile => self.handle_brewfile_key(key),
+                    Mode::Licenses => self.handle_licenses_key(key),
                 }
             }
 
@@ -231,68 +806,165 @@ impl App {
                 let nc = casks.len();
                 self.installed_formulae = formulae;
                 self.installed_casks = casks;
+                self.installed_loaded = true;
+                self.rebuild_reverse_index();
+                self.refresh_compliance();
                 self.debug_logs.push(format!("[INFO] Loaded {nf} formulae, {nc} casks"));
-                if matches!(self.tab, Tab::InstalledFormulae | Tab::InstalledCasks) {
+                if matches!(self.tab, Tab::InstalledFormulae | Tab::InstalledCasks | Tab::Leaves) {
+                    self.reset_list();
+                }
+            }
+
+            AppEvent::BrowseFormulaeLoaded { data, source } => self.catalog_loaded(data, source),
+            AppEvent::BrowseCasksLoaded { data, source } => self.catalog_loaded(data, source),
+
+            AppEvent::BrowseFailed { kind, error } => {
+                self.debug_logs.push(format!("[ERROR] {error}"));
+                match kind {
+                    PackageKind::Formula => self.browse_formulae = RemoteData::Failed(error),
+                    PackageKind::Cask => self.browse_casks = RemoteData::Failed(error),
+                }
+            }
+
+            AppEvent::TapFormulaeLoaded(data) => self.tap_catalog_loaded(data),
+            AppEvent::TapCasksLoaded(data) => self.tap_catalog_loaded(data),
+
+            AppEvent::TapCatalogFailed { kind, error } => {
+                self.debug_logs.push(format!("[ERROR] {error}"));
+                let (what, slot) = match kind {
+                    PackageKind::Formula => ("formulae", &mut self.formulae_error),
+                    PackageKind::Cask => ("casks", &mut self.casks_error),
+                };
+                self.notice = Some(format!("Couldn't load tapped {what}: {error}"));
+                // An API catalog failure is the bigger gap; keep reporting it.
+                slot.get_or_insert(error);
+            }
+
+            AppEvent::OutdatedLoaded(data) => {
+                self.debug_logs.push(format!("[INFO] Loaded {} outdated packages", data.len()));
+                self.outdated = RemoteData::Loaded(data);
+                if self.tab == Tab::Outdated {
                     self.reset_list();
                 }
             }
 
-            AppEvent::BrowseFormulaeLoaded(data) => {
-                self.debug_logs.push(format!("[INFO] Loaded {} browse formulae", data.len()));
-                self.browse_formulae = RemoteData::Loaded(data);
-                if self.tab == Tab::BrowseFormulae {
+            AppEvent::OutdatedFailed(msg) => {
+                self.debug_logs.push(format!("[ERROR] {msg}"));
+                self.outdated = RemoteData::Failed(msg);
+            }
+
+            AppEvent::DiskUsageLoaded(sizes) => {
+                self.debug_logs.push(format!(
+                    "[INFO] Measured {} formulae, {} casks on disk",
+                    sizes.formulae.len(),
+                    sizes.casks.len()
+                ));
+                let selected = self.selected_name();
+                self.disk_usage = RemoteData::Loaded(sizes);
+                self.resort_list(SortKey::Size, selected);
+            }
+
+            AppEvent::DiskUsageFailed(msg) => {
+                self.debug_logs.push(format!("[ERROR] {msg}"));
+                self.disk_usage = RemoteData::Failed(msg);
+            }
+
+            AppEvent::PopularityLoaded(counts) => {
+                self.debug_logs.push(format!(
+                    "[INFO] Loaded analytics for {} formulae, {} casks",
+                    counts.formulae.len(),
+                    counts.casks.len()
+                ));
+                let selected = self.selected_name();
+                self.popularity = RemoteData::Loaded(counts);
+                self.resort_list(SortKey::Popularity, selected);
+            }
+
+            AppEvent::PopularityFailed(msg) => {
+                self.debug_logs.push(format!("[ERROR] {msg}"));
+                self.popularity = RemoteData::Failed(msg);
+            }
+
+            AppEvent::TapsLoaded(data) => {
+                self.debug_logs.push(format!("[INFO] Loaded {} taps", data.len()));
+                self.taps = RemoteData::Loaded(data);
+                self.refresh_compliance();
+                if self.tab == Tab::Taps {
                     self.reset_list();
                 }
             }
 
-            AppEvent::BrowseCasksLoaded(data) => {
-                self.debug_logs.push(format!("[INFO] Loaded {} browse casks", data.len()));
-                self.browse_casks = RemoteData::Loaded(data);
-                if self.tab == Tab::BrowseCasks {
+            AppEvent::TapsFailed(msg) => {
+                self.debug_logs.push(format!("[ERROR] {msg}"));
+                self.taps = RemoteData::Failed(msg);
+            }
+
+            AppEvent::ServicesLoaded(data) => {
+                self.debug_logs.push(format!("[INFO] Loaded {} services", data.len()));
+                self.services = RemoteData::Loaded(data);
+                if self.tab == Tab::Services {
                     self.reset_list();
                 }
             }
 
-            AppEvent::ActionOutput(line) => {
-                if let Mode::Running { output, .. } = &mut self.mode {
-                    output.push(line);
+            AppEvent::ServicesFailed(msg) => {
+                self.debug_logs.push(format!("[ERROR] {msg}"));
+                self.services = RemoteData::Failed(msg);
+            }
+
+            AppEvent::BrewfileExported { path, entries } => {
+                let msg = format!("Wrote {entries} entries to {}", path.display());
+                self.debug_logs.push(format!("[INFO] {msg}"));
+                self.notice = Some(msg);
+            }
+
+            AppEvent::BrewfileExportFailed(msg) => {
+                self.debug_logs.push(format!("[ERROR] {msg}"));
+                self.notice = Some(format!("Export failed: {msg}"));
+            }
+
+            AppEvent::ActionOutput { job, line } => {
+                if let Some(job) = self.jobs.get_mut(job) {
+                    job.output.push(line);
                 }
             }
 
-            AppEvent::ActionDone(success) => {
-                if let Mode::Running { output, done, .. } = &mut self.mode {
-                    let msg = if success {
-                        "✓ Done. Press any key to continue.".to_string()
+            AppEvent::ActionDone { job, exit_code } => {
+                let action = self.jobs.get_mut(job).map(|j| j.action.clone());
+                if let Some(job) = self.jobs.get_mut(job) {
+                    let success = exit_code == Some(0);
+                    job.status = if success { JobStatus::Succeeded } else { JobStatus::Failed };
+                    job.exit_code = exit_code;
+                    job.output.push(if success {
+                        "✓ Done.".to_string()
                     } else {
-                        "✗ Failed. Press any key to continue.".to_string()
-                    };
-                    output.push(msg);
-                    *done = true;
+                        "✗ Failed.".to_string()
+                    });
+                    let level = if success { "INFO" } else { "ERROR" };
+                    self.debug_logs.push(format!("[{level}] {} finished ({exit_code:?})", job.command_line()));
                 }
-                // Refresh installed list after action
-                let tx = self.event_tx.clone();
-                tokio::spawn(async move {
-                    let formulae = match tokio::task::spawn_blocking(crate::brew::installed::list_formulae).await {
-                        Ok(Ok(v)) => v,
-                        Ok(Err(e)) => { let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] {e}"))).await; vec![] }
-                        Err(e) => { let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] spawn failed: {e}"))).await; vec![] }
-                    };
-                    let casks = match tokio::task::spawn_blocking(crate::brew::installed::list_casks).await {
-                        Ok(Ok(v)) => v,
-                        Ok(Err(e)) => { let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] {e}"))).await; vec![] }
-                        Err(e) => { let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] spawn failed: {e}"))).await; vec![] }
-                    };
-                    let _ = tx.send(AppEvent::InstalledLoaded { formulae, casks }).await;
-                });
+                if self.quitting {
+                    self.should_quit = true;
+                    return;
+                }
+                self.refresh_after_action(action.as_ref());
+                self.start_next_job();
             }
 
-            AppEvent::Error(msg) => {
-                self.debug_logs.push(format!("[ERROR] {msg}"));
-                // Surface errors to running output or just store for display
-                if let Mode::Running { output, done, .. } = &mut self.mode {
-                    output.push(format!("Error: {msg}"));
-                    *done = true;
+            AppEvent::ActionCancelled { job } => {
+                let action = self.jobs.get_mut(job).map(|j| j.action.clone());
+                if let Some(job) = self.jobs.get_mut(job) {
+                    job.status = JobStatus::Cancelled;
+                    job.output.push("⊘ Cancelled.".to_string());
+                    self.debug_logs.push(format!("[INFO] {} cancelled", job.command_line()));
                 }
+                if self.quitting {
+                    self.should_quit = true;
+                    return;
+                }
+                // The action may have been interrupted part-way through.
+                self.refresh_after_action(action.as_ref());
+                self.start_next_job();
             }
 
             AppEvent::DebugLog(msg) => {
@@ -302,24 +974,45 @@ impl App {
     }
 
     fn handle_normal_key(&mut self, key: crossterm::event::KeyEvent) {
-        use crossterm::event::KeyCode;
+        use crossterm::event::{KeyCode, KeyModifiers};
+        if key.modifiers == KeyModifiers::CONTROL && key.code == KeyCode::Char('f') {
+            self.toggle_search_scope();
+            return;
+        }
+        self.notice = None;
         match key.code {
-            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Char('q') => self.quit(),
+            KeyCode::Char('J') => self.open_jobs(),
+            KeyCode::Char('d') => self.open_dep_tree(),
             KeyCode::Char('?') if self.debug_mode => {
                 self.show_debug = !self.show_debug;
             }
             KeyCode::Tab => {
                 self.tab = self.tab.next();
                 self.search.clear();
+                self.marked.clear();
                 self.reset_list();
                 self.trigger_browse_load_if_needed();
             }
             KeyCode::BackTab => {
                 self.tab = self.tab.prev();
                 self.search.clear();
+                self.marked.clear();
                 self.reset_list();
                 self.trigger_browse_load_if_needed();
             }
+            KeyCode::Esc => self.marked.clear(),
+            KeyCode::Char(' ') => self.toggle_mark(),
+            KeyCode::Char('*') => self.invert_marks(),
+            KeyCode::Char('o') if self.tab.is_sortable() => {
+                self.sort_key = self.sort_key.next();
+                self.load_sort_data_if_needed();
+                self.reset_list();
+            }
+            KeyCode::Char('O') if self.tab.is_sortable() => {
+                self.sort_descending = !self.sort_descending;
+                self.reset_list();
+            }
             KeyCode::Down | KeyCode::Char('j') => self.select_next(),
             KeyCode::Up | KeyCode::Char('k') => self.select_prev(),
             KeyCode::Char('/') => {
@@ -328,63 +1021,178 @@ impl App {
                 self.reset_list();
             }
             KeyCode::Char('r') => {
-                let tx = self.event_tx.clone();
-                tokio::spawn(async move {
-                    let formulae = match tokio::task::spawn_blocking(crate::brew::installed::list_formulae).await {
-                        Ok(Ok(v)) => v,
-                        Ok(Err(e)) => { let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] {e}"))).await; vec![] }
-                        Err(e) => { let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] spawn failed: {e}"))).await; vec![] }
-                    };
-                    let casks = match tokio::task::spawn_blocking(crate::brew::installed::list_casks).await {
-                        Ok(Ok(v)) => v,
-                        Ok(Err(e)) => { let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] {e}"))).await; vec![] }
-                        Err(e) => { let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] spawn failed: {e}"))).await; vec![] }
+                spawn_installed_load(self.event_tx.clone());
+                if self.tab == Tab::Outdated || !matches!(self.outdated, RemoteData::NotLoaded) {
+                    self.load_outdated();
+                }
+                if self.tab == Tab::Taps {
+                    self.load_taps();
+                }
+                if self.tab == Tab::Services {
+                    self.load_services();
+                }
+                if self.tab == Tab::Compliance {
+                    self.reload_manifest();
+                }
+            }
+            KeyCode::Char(c @ ('f' | 'F')) if self.tab == Tab::Compliance => {
+                let targets = self.action_targets();
+                let selected: Vec<&Check> = self
+                    .compliance
+                    .iter()
+                    .filter(|check| if c == 'F' { check.outcome == Outcome::Fail } else { targets.contains(&check.label) })
+                    .collect();
+                let actions = compliance_actions(&selected);
+                if !actions.is_empty() {
+                    self.confirm(actions);
+                }
+            }
+            KeyCode::Char(c @ ('s' | 'x' | 'R' | 'n')) if self.tab == Tab::Services => {
+                let command = match c {
+                    's' => ServiceCommand::Start,
+                    'x' => ServiceCommand::Stop,
+                    'R' => ServiceCommand::Restart,
+                    _ => ServiceCommand::Run,
+                };
+                let names = self.action_targets();
+                if !names.is_empty() {
+                    self.confirm(vec![BrewAction::Service { command, names }]);
+                }
+            }
+            KeyCode::Char('B') => {
+                self.input = match &self.brewfile {
+                    Some(brewfile) => brewfile.path.display().to_string(),
+                    None => "Brewfile".to_string(),
+                };
+                self.mode = Mode::Input(InputPurpose::Import);
+            }
+            KeyCode::Char('L') => {
+                self.licenses_scroll = 0;
+                self.mode = Mode::Licenses;
+                // Licenses come from the catalog when it's available.
+                self.load_browse::<FormulaInfo>();
+            }
+            KeyCode::Char('E') => {
+                self.input = "Brewfile".to_string();
+                self.mode = Mode::Input(InputPurpose::Export);
+            }
+            KeyCode::Char('t') if self.tab == Tab::Taps => {
+                self.input.clear();
+                self.mode = Mode::Input(InputPurpose::Tap);
+            }
+            KeyCode::Char('u') if self.tab == Tab::Taps => {
+                let names = self.action_targets();
+                if !names.is_empty() {
+                    self.confirm(vec![BrewAction::Untap { names }]);
+                }
+            }
+            KeyCode::Enter if self.tab == Tab::Taps => {
+                // Browse the tap's contents, preferring formulae.
+                if let Some(tap) = self.selected_tap() {
+                    let tab = if tap.formula_names.is_empty() && !tap.cask_tokens.is_empty() {
+                        Tab::BrowseCasks
+                    } else {
+                        Tab::BrowseFormulae
                     };
-                    let _ = tx.send(AppEvent::InstalledLoaded { formulae, casks }).await;
-                });
+                    let filter = format!("tap:{}", tap.name);
+                    self.tab = tab;
+                    self.marked.clear();
+                    self.search = filter;
+                    self.trigger_browse_load_if_needed();
+                    self.reset_list();
+                }
             }
             KeyCode::Char('i') if matches!(self.tab, Tab::BrowseFormulae | Tab::BrowseCasks) => {
-                if let Some(name) = self.selected_name() {
+                let names = self.action_targets();
+                if !names.is_empty() {
                     let kind = match self.tab {
                         Tab::BrowseCasks => PackageKind::Cask,
                         _ => PackageKind::Formula,
                     };
-                    self.mode = Mode::Confirm {
-                        action: BrewAction::Install { name, kind },
-                    };
+                    self.confirm(vec![BrewAction::Install { names, kind, options: vec![] }]);
                 }
             }
-            KeyCode::Char('u') if matches!(self.tab, Tab::InstalledFormulae | Tab::InstalledCasks) => {
-                if let Some(name) = self.selected_name() {
+            KeyCode::Char('u') if matches!(self.tab, Tab::InstalledFormulae | Tab::InstalledCasks | Tab::Leaves) => {
+                let names = self.action_targets();
+                if !names.is_empty() {
                     let kind = match self.tab {
                         Tab::InstalledCasks => PackageKind::Cask,
                         _ => PackageKind::Formula,
                     };
-                    self.mode = Mode::Confirm {
-                        action: BrewAction::Uninstall { name, kind },
-                    };
+                    let ignore_dependencies = false;
+                    self.confirm(vec![BrewAction::Uninstall { names, kind, ignore_dependencies }]);
                 }
             }
-            KeyCode::Char('U') if matches!(self.tab, Tab::InstalledFormulae | Tab::InstalledCasks) => {
-                if let Some(name) = self.selected_name() {
+            KeyCode::Char('U') if matches!(self.tab, Tab::InstalledFormulae | Tab::InstalledCasks | Tab::Leaves) => {
+                let names = self.action_targets();
+                if !names.is_empty() {
                     let kind = match self.tab {
                         Tab::InstalledCasks => PackageKind::Cask,
                         _ => PackageKind::Formula,
                     };
-                    self.mode = Mode::Confirm {
-                        action: BrewAction::Upgrade { name, kind },
-                    };
+                    self.confirm(vec![BrewAction::Upgrade { names, kind }]);
                 }
             }
-            _ => {}
-        }
-    }
-
-    fn handle_search_key(&mut self, key: crossterm::event::KeyEvent) {
-        use crossterm::event::KeyCode;
-        match key.code {
-            KeyCode::Esc => {
-                self.mode = Mode::Normal;
+            KeyCode::Char('p') if matches!(self.tab, Tab::InstalledFormulae | Tab::Leaves) => {
+                let names = self.action_targets();
+                if !names.is_empty() {
+                    self.confirm(vec![BrewAction::Pin { names }]);
+                }
+            }
+            KeyCode::Char('P') if matches!(self.tab, Tab::InstalledFormulae | Tab::Leaves) => {
+                let names = self.action_targets();
+                if !names.is_empty() {
+                    self.confirm(vec![BrewAction::Unpin { names }]);
+                }
+            }
+            KeyCode::Char('A') if self.tab == Tab::Leaves => {
+                let preview: Vec<String> = crate::deps::autoremovable(&self.installed_formulae, &self.installed_casks)
+                    .into_iter()
+                    .map(String::from)
+                    .collect();
+                if !preview.is_empty() {
+                    self.confirm(vec![BrewAction::Autoremove { preview }]);
+                }
+            }
+            KeyCode::Char('U') if self.tab == Tab::Outdated => {
+                // The Outdated tab mixes formulae and casks, which brew
+                // upgrades with separate commands.
+                let targets: Vec<&OutdatedPackage> = if self.marked.is_empty() {
+                    self.selected_outdated().into_iter().collect()
+                } else {
+                    match &self.outdated {
+                        RemoteData::Loaded(v) => v.iter().filter(|p| self.is_marked(p.kind, &p.name)).collect(),
+                        _ => vec![],
+                    }
+                };
+                let mut actions = vec![];
+                for kind in [PackageKind::Formula, PackageKind::Cask] {
+                    let names: Vec<String> = targets
+                        .iter()
+                        .filter(|p| p.kind == kind)
+                        .map(|p| p.name.clone())
+                        .collect();
+                    if !names.is_empty() {
+                        actions.push(BrewAction::Upgrade { names, kind });
+                    }
+                }
+                if !actions.is_empty() {
+                    self.confirm(actions);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_search_key(&mut self, key: crossterm::event::KeyEvent) {
+        use crossterm::event::{KeyCode, KeyModifiers};
+        if key.modifiers == KeyModifiers::CONTROL && key.code == KeyCode::Char('f') {
+            self.toggle_search_scope();
+            return;
+        }
+        match key.code {
+            KeyCode::Esc => {
+                self.mode = Mode::Normal;
                 self.search.clear();
                 self.reset_list();
             }
@@ -404,75 +1212,1008 @@ impl App {
         }
     }
 
+    fn handle_input_key(&mut self, key: crossterm::event::KeyEvent, purpose: InputPurpose) {
+        use crossterm::event::KeyCode;
+        match key.code {
+            KeyCode::Esc => self.mode = Mode::Normal,
+            KeyCode::Enter => {
+                let words: Vec<String> = self.input.split_whitespace().map(String::from).collect();
+                if purpose == InputPurpose::Tap && !words.is_empty() {
+                    // Stay in the prompt so the input can be corrected.
+                    if let Err(e) = validate_tap_input(&words) {
+                        self.notice = Some(e);
+                        return;
+                    }
+                }
+                self.notice = None;
+                self.mode = Mode::Normal;
+                if words.is_empty() {
+                    return;
+                }
+                match purpose {
+                    InputPurpose::Tap => self.confirm(vec![BrewAction::Tap { names: words }]),
+                    InputPurpose::Export => self.export_brewfile(&words),
+                    InputPurpose::Import => {
+                        let path = PathBuf::from(self.input.trim());
+                        self.open_brewfile(&path);
+                    }
+                    InputPurpose::LicenseReport => {
+                        let path = PathBuf::from(self.input.trim());
+                        // Stdout belongs to the TUI.
+                        if path.as_os_str() == "-" {
+                            self.notice = Some("The report needs a file path".to_string());
+                            return;
+                        }
+                        let groups = self.license_audit();
+                        match license::write_report(&path, &groups, ReportFormat::for_path(&path)) {
+                            Ok(()) => self.notice = Some(format!("Wrote license report to {}", path.display())),
+                            Err(e) => {
+                                self.debug_logs.push(format!("[ERROR] {e}"));
+                                self.notice = Some(e);
+                            }
+                        }
+                    }
+                }
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Char(c) => self.input.push(c),
+            _ => {}
+        }
+    }
+
+    /// Writes a Brewfile in the background. `words` is a path followed by
+    /// the same flags as `taphouse export`.
+    fn export_brewfile(&mut self, words: &[String]) {
+        let mut options = ExportOptions::default();
+        let mut force = false;
+        let mut path = None;
+        for word in words {
+            match word.as_str() {
+                "--describe" => options.describe = true,
+                "--versions" => options.versions = true,
+                "--force" => force = true,
+                // Stdout belongs to the TUI.
+                "-" => {}
+                other => path = Some(PathBuf::from(other)),
+            }
+        }
+        let Some(path) = path else {
+            self.notice = Some("Export needs a file path".to_string());
+            return;
+        };
+        let tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            let target = path.clone();
+            match tokio::task::spawn_blocking(move || brewfile::export(&target, options, force)).await {
+                Ok(Ok(entries)) => { let _ = tx.send(AppEvent::BrewfileExported { path, entries }).await; }
+                Ok(Err(e)) => { let _ = tx.send(AppEvent::BrewfileExportFailed(e)).await; }
+                Err(e) => { let _ = tx.send(AppEvent::BrewfileExportFailed(format!("spawn failed: {e}"))).await; }
+            }
+        });
+    }
+
+    /// Installed formulae grouped by license, using the catalog once loaded.
+    pub fn license_audit(&self) -> Vec<LicenseGroup> {
+        let catalog = match &self.browse_formulae {
+            RemoteData::Loaded(v) => Some(v.as_slice()),
+            _ => None,
+        };
+        license::audit(catalog, &self.installed_formulae)
+    }
+
+    fn handle_licenses_key(&mut self, key: crossterm::event::KeyEvent) {
+        use crossterm::event::KeyCode;
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('L') => self.mode = Mode::Normal,
+            KeyCode::Down | KeyCode::Char('j') => self.licenses_scroll = self.licenses_scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => self.licenses_scroll = self.licenses_scroll.saturating_sub(1),
+            KeyCode::Char('e') => {
+                self.input = "licenses.md".to_string();
+                self.mode = Mode::Input(InputPurpose::LicenseReport);
+            }
+            _ => {}
+        }
+    }
+
+    /// Re-reads the manifest, e.g. after the platform team updated it.
+    fn reload_manifest(&mut self) {
+        let Some(path) = self.manifest.as_ref().map(|m| m.path.clone()) else {
+            return;
+        };
+        match Manifest::load(&path) {
+            Ok(manifest) => {
+                self.set_manifest(Some(manifest));
+                self.reset_list();
+            }
+            Err(e) => {
+                self.debug_logs.push(format!("[ERROR] {e}"));
+                self.notice = Some(e);
+            }
+        }
+    }
+
+    /// Loads a Brewfile and shows what applying it would change.
+    pub fn open_brewfile(&mut self, path: &std::path::Path) {
+        match Brewfile::load(path) {
+            Ok(brewfile) => {
+                self.debug_logs.push(format!(
+                    "[INFO] Loaded {} entries from {}",
+                    brewfile.entries.len(),
+                    path.display()
+                ));
+                self.brewfile = Some(brewfile);
+                self.brewfile_scroll = 0;
+                self.mode = Mode::Brewfile;
+                if matches!(self.taps, RemoteData::NotLoaded) {
+                    self.load_taps();
+                }
+            }
+            Err(e) => {
+                self.debug_logs.push(format!("[ERROR] {e}"));
+                self.notice = Some(e);
+            }
+        }
+    }
+
+    /// The diff between the loaded Brewfile and the installed state, once
+    /// both installed packages and taps are known.
+    pub fn brewfile_plan(&self) -> Option<Plan<'_>> {
+        let (Some(brewfile), RemoteData::Loaded(taps)) = (&self.brewfile, &self.taps) else {
+            return None;
+        };
+        if !self.installed_loaded {
+            return None;
+        }
+        Some(brewfile::plan(&brewfile.entries, &self.installed_formulae, &self.installed_casks, taps))
+    }
+
+    fn handle_brewfile_key(&mut self, key: crossterm::event::KeyEvent) {
+        use crossterm::event::KeyCode;
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::Normal,
+            KeyCode::Down | KeyCode::Char('j') => self.brewfile_scroll = self.brewfile_scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => self.brewfile_scroll = self.brewfile_scroll.saturating_sub(1),
+            KeyCode::Char(c @ ('i' | 's')) => {
+                let Some(plan) = self.brewfile_plan() else {
+                    return;
+                };
+                let actions = brewfile_actions(&plan, c == 's');
+                if !actions.is_empty() {
+                    self.confirm(actions);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn toggle_search_scope(&mut self) {
+        self.search_scope = self.search_scope.toggle();
+        self.reset_list();
+    }
+
+    /// Asks the user to confirm `actions`, checking uninstalls against the
+    /// installed formulae that depend on them. Pinned formulae are taken out
+    /// of upgrades, since brew refuses to upgrade them.
+    fn confirm(&mut self, actions: Vec<BrewAction>) {
+        let mut skipped = vec![];
+        let actions: Vec<BrewAction> = actions
+            .into_iter()
+            .filter_map(|mut action| {
+                if let BrewAction::Upgrade { names, kind: PackageKind::Formula } = &mut action {
+                    let (pinned, rest): (Vec<String>, Vec<String>) =
+                        names.drain(..).partition(|n| self.is_formula_pinned(n));
+                    skipped.extend(pinned);
+                    *names = rest;
+                    if names.is_empty() {
+                        return None;
+                    }
+                }
+                Some(action)
+            })
+            .collect();
+        // The policy checks taps and licenses against the catalogs; load
+        // them so installs refused while they're missing can be retried.
+        for action in &actions {
+            let BrewAction::Install { names, kind, .. } = action else {
+                continue;
+            };
+            if names.iter().any(|name| self.config.policy.needs_catalog(*kind, name)) {
+                match kind {
+                    PackageKind::Formula => self.load_browse::<FormulaInfo>(),
+                    PackageKind::Cask => self.load_browse::<CaskInfo>(),
+                }
+            }
+        }
+        let (actions, denied) = self.apply_policy(actions);
+
+        let mut dependents = vec![];
+        for action in &actions {
+            let BrewAction::Uninstall { kind, .. } = action else {
+                continue;
+            };
+            for name in action.names() {
+                let used_by: Vec<String> = self
+                    .reverse_index
+                    .used_by_installed(*kind, name)
+                    .iter()
+                    .filter(|n| !action.names().contains(n))
+                    .cloned()
+                    .collect();
+                if !used_by.is_empty() {
+                    dependents.push(Dependents { name: name.clone(), used_by });
+                }
+            }
+        }
+        self.mode = Mode::Confirm { actions, dependents, skipped, denied };
+    }
+
+    /// Why the policy forbids installing `name`, if it does. The tap and
+    /// license come from the catalog; while it's unavailable, a package the
+    /// policy needs them for is refused rather than let through unchecked.
+    fn policy_violation(&self, kind: PackageKind, name: &str) -> Option<String> {
+        let policy = &self.config.policy;
+        if let Some(reason) = policy.check_name(kind, name) {
+            return Some(reason);
+        }
+        let (label, entry) = match kind {
+            PackageKind::Formula => (
+                "formula",
+                match &self.browse_formulae {
+                    RemoteData::Loaded(catalog) => Ok(catalog
+                        .iter()
+                        .find(|f| catalog_matches(name, &f.tap, &f.name, f.aliases.iter().chain(&f.oldnames)))
+                        .map(|f| (f.tap.as_str(), f.license.as_deref()))),
+                    RemoteData::Failed(_) => Err("failed to load"),
+                    _ => Err("is still loading"),
+                },
+            ),
+            PackageKind::Cask => (
+                "cask",
+                match &self.browse_casks {
+                    RemoteData::Loaded(catalog) => Ok(catalog
+                        .iter()
+                        .find(|c| catalog_matches(name, &c.tap, &c.token, c.old_tokens.iter()))
+                        .map(|c| (c.tap.as_str(), None))),
+                    RemoteData::Failed(_) => Err("failed to load"),
+                    _ => Err("is still loading"),
+                },
+            ),
+        };
+        match entry {
+            Ok(Some((tap, license))) => policy.check_package(kind, name, Some(tap).filter(|t| !t.is_empty()), license),
+            Ok(None) if policy.needs_catalog(kind, name) => {
+                Some(format!("Not in the {label} catalog, so its tap and license can't be checked"))
+            }
+            Err(state) if policy.needs_catalog(kind, name) => {
+                Some(format!("The {label} catalog {state}, so its tap and license can't be checked"))
+            }
+            _ => policy.check_package(kind, name, None, None),
+        }
+    }
+
+    /// Takes the installs and taps the policy forbids out of `actions`.
+    fn apply_policy(&self, actions: Vec<BrewAction>) -> (Vec<BrewAction>, Vec<Violation>) {
+        let mut denied = vec![];
+        let actions = actions
+            .into_iter()
+            .filter_map(|mut action| {
+                match &mut action {
+                    BrewAction::Install { names, kind, .. } => {
+                        names.retain(|name| match self.policy_violation(*kind, name) {
+                            Some(reason) => {
+                                denied.push(Violation { name: name.clone(), reason });
+                                false
+                            }
+                            None => true,
+                        });
+                        if names.is_empty() {
+                            return None;
+                        }
+                    }
+                    // The tap, optionally followed by its URL.
+                    BrewAction::Tap { names } => {
+                        let tap = names.first()?;
+                        if let Some(reason) = self.config.policy.check_tap(tap) {
+                            denied.push(Violation { name: tap.clone(), reason });
+                            return None;
+                        }
+                    }
+                    _ => {}
+                }
+                Some(action)
+            })
+            .collect();
+        (actions, denied)
+    }
+
     fn handle_confirm_key(&mut self, key: crossterm::event::KeyEvent) {
         use crossterm::event::KeyCode;
-        let action = match &self.mode {
-            Mode::Confirm { action } => action.clone(),
+        let (mut actions, blocked) = match &self.mode {
+            Mode::Confirm { actions, dependents, .. } => (actions.clone(), !dependents.is_empty()),
             _ => return,
         };
         match key.code {
-            KeyCode::Char('y') | KeyCode::Char('Y') => {
-                let tx = self.event_tx.clone();
-                let action_clone = action.clone();
-                tokio::spawn(async move {
-                    crate::brew::actions::run_brew_action(
-                        action_clone.verb(),
-                        action_clone.name(),
-                        action_clone.kind(),
-                        tx,
-                    )
-                    .await;
-                });
-                self.mode = Mode::Running {
-                    action,
-                    output: vec![],
-                    done: false,
-                };
+            KeyCode::Char('y') | KeyCode::Char('Y') if !blocked => {}
+            KeyCode::Char('!') if blocked => {
+                for action in &mut actions {
+                    if let BrewAction::Uninstall { ignore_dependencies, .. } = action {
+                        *ignore_dependencies = true;
+                    }
+                }
             }
             KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
                 self.mode = Mode::Normal;
+                return;
+            }
+            _ => return,
+        }
+        // Checked again so nothing reaches the job queue without passing
+        // the policy.
+        let (actions, denied) = self.apply_policy(actions);
+        for violation in &denied {
+            self.debug_logs.push(format!("[ERROR] Blocked by policy: {}: {}", violation.name, violation.reason));
+        }
+        self.enqueue_actions(actions);
+    }
+
+    fn enqueue_actions(&mut self, actions: Vec<BrewAction>) {
+        for action in actions {
+            let id = self.jobs.enqueue(action);
+            self.debug_logs.push(format!("[INFO] Queued job #{id}"));
+        }
+        self.marked.clear();
+        self.mode = Mode::Normal;
+        self.start_next_job();
+    }
+
+    fn handle_jobs_key(&mut self, key: crossterm::event::KeyEvent) {
+        use crossterm::event::KeyCode;
+        let len = self.jobs.jobs().len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('J') => self.mode = Mode::Normal,
+            KeyCode::Down | KeyCode::Char('j') if len > 0 => {
+                let i = self.jobs_state.selected().map(|i| (i + 1).min(len - 1)).unwrap_or(0);
+                self.jobs_state.select(Some(i));
+            }
+            KeyCode::Up | KeyCode::Char('k') if len > 0 => {
+                let i = self.jobs_state.selected().map(|i| i.saturating_sub(1)).unwrap_or(0);
+                self.jobs_state.select(Some(i));
+            }
+            KeyCode::Char('x') => {
+                let selected = self.jobs_state.selected().and_then(|i| self.jobs.jobs().get(i));
+                if let Some(id) = selected.map(|j| j.id) {
+                    if self.jobs.cancel(id) {
+                        self.debug_logs.push(format!("[INFO] Cancelling job #{id}"));
+                    }
+                }
+            }
+            KeyCode::Char('c') => {
+                self.jobs.clear_finished();
+                self.select_active_job();
             }
             _ => {}
         }
     }
 
+    fn open_dep_tree(&mut self) {
+        let Some(name) = self.selected_formula_name() else {
+            return;
+        };
+        self.dep_tree = DepTree::new(name);
+        self.mode = Mode::DepTree;
+        // Installed formulae are enough to start with, but only the catalog
+        // knows the dependencies of formulae that aren't installed.
+        self.load_browse::<FormulaInfo>();
+    }
+
+    fn handle_dep_tree_key(&mut self, key: crossterm::event::KeyEvent) {
+        use crossterm::event::KeyCode;
+        let rows = self.dep_tree.rows(&self.dep_graph());
+        let selected = self.dep_tree.state.selected().unwrap_or(0).min(rows.len().saturating_sub(1));
+        let Some(row) = rows.get(selected) else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('d') => self.mode = Mode::Normal,
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.dep_tree.state.select(Some((selected + 1).min(rows.len() - 1)));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.dep_tree.state.select(Some(selected.saturating_sub(1)));
+            }
+            KeyCode::Char(' ') if row.has_children => {
+                self.dep_tree.set_expanded(&row.path, !row.expanded);
+            }
+            KeyCode::Right | KeyCode::Char('l') if row.has_children => {
+                self.dep_tree.set_expanded(&row.path, true);
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                if row.expanded {
+                    self.dep_tree.set_expanded(&row.path, false);
+                } else if let Some(parent) = rows.iter().position(|r| r.path == row.path[..row.path.len() - 1]) {
+                    self.dep_tree.state.select(Some(parent));
+                }
+            }
+            KeyCode::Enter => {
+                let name = row.name.clone();
+                let tab = if self.is_formula_installed(&name) {
+                    Tab::InstalledFormulae
+                } else {
+                    Tab::BrowseFormulae
+                };
+                self.mode = Mode::Normal;
+                self.show_package(tab, &name);
+            }
+            _ => {}
+        }
+    }
+
+    /// Switches to `tab` and selects `name` in it, clearing any search that
+    /// could hide it.
+    fn show_package(&mut self, tab: Tab, name: &str) {
+        self.tab = tab;
+        self.search.clear();
+        self.marked.clear();
+        self.trigger_browse_load_if_needed();
+        let i = self.visible_items().iter().position(|item| item.name == name);
+        self.list_state.select(i.or(Some(0)));
+    }
+
+    fn open_jobs(&mut self) {
+        self.mode = Mode::Jobs;
+        self.select_active_job();
+    }
+
+    /// Selects the running job, or the most recent one if nothing is running.
+    fn select_active_job(&mut self) {
+        let jobs = self.jobs.jobs();
+        let i = jobs
+            .iter()
+            .position(|j| j.status == JobStatus::Running)
+            .or_else(|| jobs.len().checked_sub(1));
+        self.jobs_state.select(i);
+    }
+
+    /// Quits, first stopping a running job the same way `x` in the jobs view
+    /// does, so brew and its children can clean up. The app exits once the
+    /// job reports back.
+    fn quit(&mut self) {
+        let Some(running) = self.jobs.running().map(|j| j.id) else {
+            self.should_quit = true;
+            return;
+        };
+        if self.quitting {
+            return;
+        }
+        self.quitting = true;
+        let queued: Vec<JobId> = self
+            .jobs
+            .jobs()
+            .iter()
+            .filter(|j| j.status == JobStatus::Queued)
+            .map(|j| j.id)
+            .collect();
+        for id in queued {
+            self.jobs.cancel(id);
+        }
+        self.jobs.cancel(running);
+        self.debug_logs.push(format!("[INFO] Stopping job #{running} before quitting"));
+        self.notice = Some(format!("Stopping job #{running} before quitting…"));
+    }
+
+    /// Starts the next queued job in the background if nothing is running.
+    fn start_next_job(&mut self) {
+        let Some(job) = self.jobs.start_next() else {
+            return;
+        };
+        let (cancel_tx, cancel_rx) = tokio::sync::oneshot::channel();
+        job.cancel = Some(cancel_tx);
+        let id = job.id;
+        let args = job.action.args();
+        let command_line = job.command_line();
+        self.debug_logs.push(format!("[INFO] Running job #{id}: {command_line}"));
+        let tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            crate::brew::actions::run_brew_action(id, args, cancel_rx, tx).await;
+        });
+    }
+
     fn trigger_browse_load_if_needed(&mut self) {
         match self.tab {
-            Tab::BrowseFormulae => {
-                if matches!(self.browse_formulae, RemoteData::NotLoaded) {
-                    self.browse_formulae = RemoteData::Loading;
-                    let tx = self.event_tx.clone();
-                    tokio::spawn(async move {
-                        let client = reqwest::Client::new();
-                        match crate::brew::api::fetch_formulae(&client).await {
-                            Ok(data) => {
-                                let _ = tx.send(AppEvent::BrowseFormulaeLoaded(data)).await;
-                            }
-                            Err(e) => {
-                                let _ = tx.send(AppEvent::Error(e)).await;
-                            }
-                        }
-                    });
+            Tab::BrowseFormulae => self.load_browse::<FormulaInfo>(),
+            Tab::BrowseCasks => self.load_browse::<CaskInfo>(),
+            Tab::Outdated => {
+                if matches!(self.outdated, RemoteData::NotLoaded) {
+                    self.load_outdated();
                 }
             }
-            Tab::BrowseCasks => {
-                if matches!(self.browse_casks, RemoteData::NotLoaded) {
-                    self.browse_casks = RemoteData::Loading;
-                    let tx = self.event_tx.clone();
-                    tokio::spawn(async move {
-                        let client = reqwest::Client::new();
-                        match crate::brew::api::fetch_casks(&client).await {
-                            Ok(data) => {
-                                let _ = tx.send(AppEvent::BrowseCasksLoaded(data)).await;
-                            }
-                            Err(e) => {
-                                let _ = tx.send(AppEvent::Error(e)).await;
-                            }
-                        }
-                    });
+            Tab::Taps => {
+                if matches!(self.taps, RemoteData::NotLoaded) {
+                    self.load_taps();
+                }
+            }
+            Tab::Services => {
+                if matches!(self.services, RemoteData::NotLoaded) {
+                    self.load_services();
+                }
+            }
+            Tab::Compliance => {
+                if matches!(self.taps, RemoteData::NotLoaded) {
+                    self.load_taps();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Sizes and analytics are only needed for sorting, so they are fetched
+    /// the first time their sort key is picked.
+    fn load_sort_data_if_needed(&mut self) {
+        match self.sort_key {
+            SortKey::Size if matches!(self.disk_usage, RemoteData::NotLoaded) => self.load_disk_usage(),
+            SortKey::Popularity if matches!(self.popularity, RemoteData::NotLoaded) => {
+                self.popularity = RemoteData::Loading;
+                let tx = self.event_tx.clone();
+                let api_domain = self.config.api_domain.clone();
+                tokio::spawn(async move {
+                    let client = crate::brew::api::client();
+                    match crate::brew::api::fetch_popularity(&client, &api_domain).await {
+                        Ok(counts) => { let _ = tx.send(AppEvent::PopularityLoaded(counts)).await; }
+                        Err(e) => { let _ = tx.send(AppEvent::PopularityFailed(e)).await; }
+                    }
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn load_disk_usage(&mut self) {
+        if !matches!(self.disk_usage, RemoteData::Loaded(_)) {
+            self.disk_usage = RemoteData::Loading;
+        }
+        let tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            match tokio::task::spawn_blocking(crate::brew::installed::disk_usage).await {
+                Ok(Ok(v)) => { let _ = tx.send(AppEvent::DiskUsageLoaded(v)).await; }
+                Ok(Err(e)) => { let _ = tx.send(AppEvent::DiskUsageFailed(e)).await; }
+                Err(e) => { let _ = tx.send(AppEvent::DiskUsageFailed(format!("spawn failed: {e}"))).await; }
+            }
+        });
+    }
+
+    fn load_browse<T: Catalog>(&mut self) {
+        let catalog = T::slots(self).0;
+        if !matches!(catalog, RemoteData::NotLoaded) {
+            return;
+        }
+        *catalog = RemoteData::Loading;
+        let tx = self.event_tx.clone();
+        let api_domain = self.config.api_domain.clone();
+        let use_homebrew_cache = self.config.use_homebrew_cache;
+        tokio::spawn(async move {
+            // Show the cached catalog straight away; the fetch replaces it
+            // once the server has said whether it's current.
+            let cached = if use_homebrew_cache { None } else { T::cached(&api_domain) };
+            let shown = cached.is_some();
+            if let Some(data) = cached {
+                let _ = tx.send(T::loaded(data, CatalogSource::Cached)).await;
+            }
+            let client = crate::brew::api::client();
+            match T::fetch(&client, &api_domain, use_homebrew_cache).await {
+                Ok((data, source)) => {
+                    let _ = tx.send(T::loaded(data, source)).await;
                 }
+                // Keep showing the cached copy.
+                Err(error) if shown => {
+                    let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] {error}"))).await;
+                }
+                Err(error) => {
+                    let _ = tx.send(AppEvent::BrowseFailed { kind: T::KIND, error }).await;
+                }
+            }
+            load_tap_catalog::<T>(tx).await;
+        });
+    }
+
+    fn catalog_loaded<T: Catalog>(&mut self, data: Vec<T>, source: CatalogSource) {
+        self.debug_logs.push(format!("[INFO] Loaded {} browse {} ({source:?})", data.len(), T::PLURAL));
+        // A refresh of the cached catalog keeps the cursor in place.
+        let selected = matches!(T::slots(self).0, RemoteData::Loaded(_)).then(|| self.selected_name());
+        let (catalog, catalog_source, error) = T::slots(self);
+        *catalog = RemoteData::Loaded(data);
+        *catalog_source = Some(source);
+        *error = None;
+        self.rebuild_reverse_index();
+        if self.tab == T::TAB {
+            match selected {
+                Some(selected) => self.reselect(selected),
+                None => self.reset_list(),
+            }
+        }
+    }
+
+    /// Merges packages from third-party taps into the catalog, replacing
+    /// the ones merged before.
+    fn tap_catalog_loaded<T: Catalog>(&mut self, data: Vec<T>) {
+        self.debug_logs.push(format!("[INFO] Loaded {} {} from taps", data.len(), T::PLURAL));
+        let (catalog, source, error) = T::slots(self);
+        if source.is_some() {
+            // The API catalog loaded, so only a tap failure was left.
+            *error = None;
+        }
+        let mut notice = None;
+        match catalog {
+            RemoteData::Loaded(v) => {
+                v.retain(|entry| !entry.local_tap());
+                for mut entry in data {
+                    // Keep a tap package that shadows a core one distinct,
+                    // and installable, by its qualified name.
+                    if v.iter().any(|existing| existing.search_name() == entry.search_name()) {
+                        entry.qualify();
+                    }
+                    v.push(entry);
+                }
+            }
+            // The catalog failed to load; the taps still work.
+            RemoteData::Failed(e) if !data.is_empty() => {
+                notice = Some(format!("{} catalog unavailable, listing tapped {} only: {e}", T::LABEL, T::PLURAL));
+                *error = Some(std::mem::take(e));
+                *catalog = RemoteData::Loaded(data);
             }
             _ => {}
         }
+        if notice.is_some() {
+            self.notice = notice;
+        }
+        self.rebuild_reverse_index();
+        if self.tab == T::TAB && self.list_state.selected().is_none() {
+            self.reset_list();
+        }
+    }
+
+    fn load_taps(&mut self) {
+        if !matches!(self.taps, RemoteData::Loaded(_)) {
+            self.taps = RemoteData::Loading;
+        }
+        let tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            match tokio::task::spawn_blocking(crate::brew::taps::list_taps).await {
+                Ok(Ok(v)) => { let _ = tx.send(AppEvent::TapsLoaded(v)).await; }
+                Ok(Err(e)) => { let _ = tx.send(AppEvent::TapsFailed(e)).await; }
+                Err(e) => { let _ = tx.send(AppEvent::TapsFailed(format!("spawn failed: {e}"))).await; }
+            }
+        });
+    }
+
+    fn load_services(&mut self) {
+        if !matches!(self.services, RemoteData::Loaded(_)) {
+            self.services = RemoteData::Loading;
+        }
+        let tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            match tokio::task::spawn_blocking(crate::brew::services::list_services).await {
+                Ok(Ok(v)) => { let _ = tx.send(AppEvent::ServicesLoaded(v)).await; }
+                Ok(Err(e)) => { let _ = tx.send(AppEvent::ServicesFailed(e)).await; }
+                Err(e) => { let _ = tx.send(AppEvent::ServicesFailed(format!("spawn failed: {e}"))).await; }
+            }
+        });
+    }
+
+    /// Reloads everything a finished (or interrupted) brew action may have
+    /// changed. Data that was never loaded stays unloaded.
+    fn refresh_after_action(&mut self, action: Option<&BrewAction>) {
+        spawn_installed_load(self.event_tx.clone());
+        if !matches!(self.outdated, RemoteData::NotLoaded) {
+            self.load_outdated();
+        }
+        if !matches!(self.disk_usage, RemoteData::NotLoaded) {
+            self.load_disk_usage();
+        }
+        if !matches!(self.taps, RemoteData::NotLoaded) {
+            self.load_taps();
+        }
+        if !matches!(self.services, RemoteData::NotLoaded) {
+            self.load_services();
+        }
+        if matches!(action, Some(BrewAction::Tap { .. } | BrewAction::Untap { .. })) {
+            if matches!(self.browse_formulae, RemoteData::Loaded(_)) {
+                tokio::spawn(load_tap_catalog::<FormulaInfo>(self.event_tx.clone()));
+            }
+            if matches!(self.browse_casks, RemoteData::Loaded(_)) {
+                tokio::spawn(load_tap_catalog::<CaskInfo>(self.event_tx.clone()));
+            }
+        }
+    }
+
+    fn load_outdated(&mut self) {
+        if !matches!(self.outdated, RemoteData::Loaded(_)) {
+            self.outdated = RemoteData::Loading;
+        }
+        let tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            match tokio::task::spawn_blocking(crate::brew::installed::list_outdated).await {
+                Ok(Ok(v)) => { let _ = tx.send(AppEvent::OutdatedLoaded(v)).await; }
+                Ok(Err(e)) => { let _ = tx.send(AppEvent::OutdatedFailed(e)).await; }
+                Err(e) => { let _ = tx.send(AppEvent::OutdatedFailed(format!("spawn failed: {e}"))).await; }
+            }
+        });
+    }
+}
+
+/// Checks a tap prompt: `user/repo`, optionally followed by a clone URL.
+fn validate_tap_input(words: &[String]) -> Result<(), String> {
+    let (name, url) = match words {
+        [name] => (name, None),
+        [name, url] => (name, Some(url)),
+        _ => return Err("Enter one tap: user/repo [URL]".to_string()),
+    };
+    let valid_name = matches!(name.split_once('/'), Some((user, repo)) if !user.is_empty() && !repo.is_empty() && !repo.contains('/'));
+    if !valid_name {
+        return Err(format!("{name} is not a tap name (user/repo)"));
+    }
+    if let Some(url) = url {
+        let remote = ["https://", "http://", "ssh://", "git://", "file://"]
+            .iter()
+            .any(|scheme| url.strip_prefix(scheme).is_some_and(|rest| !rest.is_empty()));
+        // `git@github.com:user/repo.git` or a local clone.
+        let scp_like = matches!(url.split_once(':'), Some((host, path)) if host.contains('@') && !path.is_empty());
+        if !remote && !scp_like && !url.starts_with('/') {
+            return Err(format!("{url} is not a git URL"));
+        }
+    }
+    Ok(())
+}
+
+/// Whether the catalog entry `entry` from `tap` is what `name` installs.
+/// `user/repo/name` must come from that tap, even if core has a package of
+/// the same name; a bare name also matches aliases and old names.
+fn catalog_matches<'a>(name: &str, tap: &str, entry: &str, mut others: impl Iterator<Item = &'a String>) -> bool {
+    match name.rsplit_once('/') {
+        Some((tap_name, short)) => tap.eq_ignore_ascii_case(tap_name) && crate::deps::short_name(entry) == short,
+        None => entry == name || others.any(|o| o == name),
+    }
+}
+
+/// The actions that bring the system in line with a Brewfile plan: taps
+/// first, then installs, then service restarts. With `sync`, packages and
+/// taps missing from the Brewfile are removed afterwards.
+fn brewfile_actions(plan: &Plan, sync: bool) -> Vec<BrewAction> {
+    let mut actions = vec![];
+    for entry in plan.install.iter().filter(|e| e.kind == EntryKind::Tap) {
+        let names = std::iter::once(entry.name.clone()).chain(entry.url.clone()).collect();
+        actions.push(BrewAction::Tap { names });
+    }
+    for (entry_kind, kind) in [(EntryKind::Brew, PackageKind::Formula), (EntryKind::Cask, PackageKind::Cask)] {
+        let entries = plan.install.iter().copied().filter(|e| e.kind == entry_kind);
+        let (plain, with_args): (Vec<&Entry>, Vec<_>) = entries.partition(|e| e.args.is_empty());
+        if !plain.is_empty() {
+            let names = plain.iter().map(|e| e.name.clone()).collect();
+            actions.push(BrewAction::Install { names, kind, options: vec![] });
+        }
+        // Each package gets its own flags.
+        for entry in with_args {
+            actions.push(BrewAction::Install { names: vec![entry.name.clone()], kind, options: entry.args.clone() });
+        }
+    }
+    let restarts: Vec<String> = plan
+        .install
+        .iter()
+        .filter(|e| e.restart_service != RestartService::Never)
+        .chain(plan.satisfied.iter().filter(|e| e.restart_service == RestartService::Always))
+        .map(|e| e.name.clone())
+        .collect();
+    if !restarts.is_empty() {
+        actions.push(BrewAction::Service { command: ServiceCommand::Restart, names: restarts });
+    }
+    if sync {
+        let removals = |kind: EntryKind| -> Vec<String> {
+            plan.remove.iter().filter(|(k, _)| *k == kind).map(|(_, name)| name.clone()).collect()
+        };
+        let formulae = removals(EntryKind::Brew);
+        if !formulae.is_empty() {
+            actions.push(BrewAction::Uninstall { names: formulae, kind: PackageKind::Formula, ignore_dependencies: false });
+        }
+        let casks = removals(EntryKind::Cask);
+        if !casks.is_empty() {
+            actions.push(BrewAction::Uninstall { names: casks, kind: PackageKind::Cask, ignore_dependencies: false });
+        }
+        let taps = removals(EntryKind::Tap);
+        if !taps.is_empty() {
+            actions.push(BrewAction::Untap { names: taps });
+        }
+    }
+    actions
+}
+
+/// The actions that fix `checks`: install what's required (or optional and
+/// picked), upgrade what's too old, and remove what's forbidden. Passing
+/// checks need nothing.
+fn compliance_actions(checks: &[&Check]) -> Vec<BrewAction> {
+    let mut taps = vec![];
+    let mut untaps = vec![];
+    let mut by_kind: HashMap<(&str, PackageKind), Vec<String>> = HashMap::new();
+    for check in checks {
+        let kind = match check.kind {
+            EntryKind::Cask => PackageKind::Cask,
+            _ => PackageKind::Formula,
+        };
+        let verb = match (&check.rule, check.outcome) {
+            (Rule::Required, Outcome::Fail) | (Rule::Optional { .. }, Outcome::Skipped) => "install",
+            (Rule::Forbidden, Outcome::Fail) => "uninstall",
+            (Rule::MinimumVersion { .. }, Outcome::Fail) => "upgrade",
+            _ => continue,
+        };
+        match (check.kind, verb) {
+            (EntryKind::Tap, "install") => taps.push(check.name.clone()),
+            (EntryKind::Tap, _) => untaps.push(check.name.clone()),
+            _ => by_kind.entry((verb, kind)).or_default().push(check.name.clone()),
+        }
+    }
+    let mut actions: Vec<BrewAction> = taps.into_iter().map(|name| BrewAction::Tap { names: vec![name] }).collect();
+    for verb in ["install", "upgrade", "uninstall"] {
+        for kind in [PackageKind::Formula, PackageKind::Cask] {
+            let Some(names) = by_kind.remove(&(verb, kind)) else {
+                continue;
+            };
+            actions.push(match verb {
+                "install" => BrewAction::Install { names, kind, options: vec![] },
+                "upgrade" => BrewAction::Upgrade { names, kind },
+                _ => BrewAction::Uninstall { names, kind, ignore_dependencies: false },
+            });
+        }
+    }
+    if !untaps.is_empty() {
+        actions.push(BrewAction::Untap { names: untaps });
+    }
+    actions
+}
+
+/// Loads installed formulae and casks in the background and reports them as
+/// `AppEvent::InstalledLoaded`. Failures are logged and yield empty lists.
+pub fn spawn_installed_load(tx: mpsc::Sender<AppEvent>) {
+    tokio::spawn(async move {
+        let installed = match tokio::task::spawn_blocking(crate::brew::installed::list_installed).await {
+            Ok(Ok(v)) => v,
+            Ok(Err(e)) => {
+                let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] {e}"))).await;
+                InstalledPackages { formulae: vec![], casks: vec![] }
+            }
+            Err(e) => {
+                let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] spawn failed: {e}"))).await;
+                InstalledPackages { formulae: vec![], casks: vec![] }
+            }
+        };
+        let _ = tx
+            .send(AppEvent::InstalledLoaded {
+                formulae: installed.formulae,
+                casks: installed.casks,
+            })
+            .await;
+    });
+}
+
+/// Loads packages from third-party taps after the catalog, so the catalog
+/// shows up without waiting on `brew info`.
+async fn load_tap_catalog<T: Catalog>(tx: mpsc::Sender<AppEvent>) {
+    let kind = T::KIND;
+    match tokio::task::spawn_blocking(T::from_taps).await {
+        Ok(Ok(v)) => { let _ = tx.send(T::tap_loaded(v)).await; }
+        Ok(Err(error)) => { let _ = tx.send(AppEvent::TapCatalogFailed { kind, error }).await; }
+        Err(e) => { let _ = tx.send(AppEvent::TapCatalogFailed { kind, error: format!("spawn failed: {e}") }).await; }
+    }
+}
+
+/// A browse catalog, formulae or casks: loaded from the API, then merged
+/// with what third-party taps provide.
+trait Catalog: Searchable + Sized + Send + 'static {
+    const KIND: PackageKind;
+    const TAB: Tab;
+    /// For messages: "Formula", "formulae".
+    const LABEL: &'static str;
+    const PLURAL: &'static str;
+
+    /// The catalog in `app`, where it came from, and the error of a half
+    /// that failed to load.
+    fn slots(app: &mut App) -> (&mut RemoteData<Vec<Self>>, &mut Option<CatalogSource>, &mut Option<String>);
+    fn cached(api_domain: &str) -> Option<Vec<Self>>;
+    fn fetch(
+        client: &reqwest::Client,
+        api_domain: &str,
+        use_homebrew_cache: bool,
+    ) -> impl Future<Output = Result<(Vec<Self>, CatalogSource), String>> + Send;
+    fn from_taps() -> Result<Vec<Self>, String>;
+    fn loaded(data: Vec<Self>, source: CatalogSource) -> AppEvent;
+    fn tap_loaded(data: Vec<Self>) -> AppEvent;
+    /// Whether this entry came from a local tap rather than the API.
+    fn local_tap(&self) -> bool;
+    /// Renames a tap entry that shadows an API one to its qualified name.
+    fn qualify(&mut self);
+}
+
+impl Catalog for FormulaInfo {
+    const KIND: PackageKind = PackageKind::Formula;
+    const TAB: Tab = Tab::BrowseFormulae;
+    const LABEL: &'static str = "Formula";
+    const PLURAL: &'static str = "formulae";
+
+    fn slots(app: &mut App) -> (&mut RemoteData<Vec<Self>>, &mut Option<CatalogSource>, &mut Option<String>) {
+        (&mut app.browse_formulae, &mut app.formulae_source, &mut app.formulae_error)
+    }
+
+    fn cached(api_domain: &str) -> Option<Vec<Self>> {
+        crate::brew::api::cached_formulae(api_domain)
+    }
+
+    fn fetch(
+        client: &reqwest::Client,
+        api_domain: &str,
+        use_homebrew_cache: bool,
+    ) -> impl Future<Output = Result<(Vec<Self>, CatalogSource), String>> + Send {
+        crate::brew::api::fetch_formulae(client, api_domain, use_homebrew_cache)
+    }
+
+    fn from_taps() -> Result<Vec<Self>, String> {
+        crate::brew::taps::tap_formulae()
+    }
+
+    fn loaded(data: Vec<Self>, source: CatalogSource) -> AppEvent {
+        AppEvent::BrowseFormulaeLoaded { data, source }
+    }
+
+    fn tap_loaded(data: Vec<Self>) -> AppEvent {
+        AppEvent::TapFormulaeLoaded(data)
+    }
+
+    fn local_tap(&self) -> bool {
+        self.local_tap
+    }
+
+    fn qualify(&mut self) {
+        self.name = self.full_name.clone();
+    }
+}
+
+impl Catalog for CaskInfo {
+    const KIND: PackageKind = PackageKind::Cask;
+    const TAB: Tab = Tab::BrowseCasks;
+    const LABEL: &'static str = "Cask";
+    const PLURAL: &'static str = "casks";
+
+    fn slots(app: &mut App) -> (&mut RemoteData<Vec<Self>>, &mut Option<CatalogSource>, &mut Option<String>) {
+        (&mut app.browse_casks, &mut app.casks_source, &mut app.casks_error)
+    }
+
+    fn cached(api_domain: &str) -> Option<Vec<Self>> {
+        crate::brew::api::cached_casks(api_domain)
+    }
+
+    fn fetch(
+        client: &reqwest::Client,
+        api_domain: &str,
+        use_homebrew_cache: bool,
+    ) -> impl Future<Output = Result<(Vec<Self>, CatalogSource), String>> + Send {
+        crate::brew::api::fetch_casks(client, api_domain, use_homebrew_cache)
+    }
+
+    fn from_taps() -> Result<Vec<Self>, String> {
+        crate::brew::taps::tap_casks()
+    }
+
+    fn loaded(data: Vec<Self>, source: CatalogSource) -> AppEvent {
+        AppEvent::BrowseCasksLoaded { data, source }
+    }
+
+    fn tap_loaded(data: Vec<Self>) -> AppEvent {
+        AppEvent::TapCasksLoaded(data)
+    }
+
+    fn local_tap(&self) -> bool {
+        self.local_tap
+    }
+
+    fn qualify(&mut self) {
+        self.token = format!("{}/{}", self.tap, self.token);
     }
 }
@@ -1,63 +1,100 @@
-use std::process::Stdio;
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
-use tokio::sync::mpsc;
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, oneshot};
 
 use crate::app::AppEvent;
-use crate::brew::types::PackageKind;
+use crate::jobs::JobId;
 
+/// How long to wait after each signal before escalating to the next one
+/// when cancelling.
+const CANCEL_GRACE: Duration = Duration::from_secs(5);
+
+/// Runs `brew <args...>` (see `BrewAction::args`) for job `job`, streaming
+/// its output as `AppEvent::ActionOutput` and finishing with
+/// `AppEvent::ActionDone`, or `AppEvent::ActionCancelled` if `cancel` fires.
 pub async fn run_brew_action(
-    action: &str,
-    name: &str,
-    kind: PackageKind,
+    job: JobId,
+    args: Vec<String>,
+    mut cancel: oneshot::Receiver<()>,
     tx: mpsc::Sender<AppEvent>,
 ) {
-    let kind_flag = match kind {
-        PackageKind::Cask => Some("--cask"),
-        PackageKind::Formula => None,
-    };
-
     let mut cmd = Command::new("brew");
-    cmd.arg(action);
-    if let Some(flag) = kind_flag {
-        cmd.arg(flag);
-    }
-    cmd.arg(name);
+    cmd.args(&args);
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
+    // Own process group so cancellation reaches brew's children (curl, git,
+    // installers) too. Quitting cancels the job and waits for it; killing
+    // brew on drop is only a last resort, e.g. after a panic.
+    cmd.process_group(0);
+    cmd.kill_on_drop(true);
 
     let mut child = match cmd.spawn() {
         Ok(c) => c,
         Err(e) => {
-            let _ = tx.send(AppEvent::ActionOutput(format!("Error: {e}"))).await;
-            let _ = tx.send(AppEvent::ActionDone(false)).await;
+            let line = format!("Error: {e}");
+            let _ = tx.send(AppEvent::ActionOutput { job, line }).await;
+            let _ = tx.send(AppEvent::ActionDone { job, exit_code: None }).await;
             return;
         }
     };
 
+    let mut readers = vec![];
+
     // Stream stdout
     if let Some(stdout) = child.stdout.take() {
         let tx2 = tx.clone();
-        tokio::spawn(async move {
+        readers.push(tokio::spawn(async move {
             let mut reader = BufReader::new(stdout).lines();
             while let Ok(Some(line)) = reader.next_line().await {
-                let _ = tx2.send(AppEvent::ActionOutput(line)).await;
+                let _ = tx2.send(AppEvent::ActionOutput { job, line }).await;
             }
-        });
+        }));
     }
 
     // Stream stderr
     if let Some(stderr) = child.stderr.take() {
         let tx3 = tx.clone();
-        tokio::spawn(async move {
+        readers.push(tokio::spawn(async move {
             let mut reader = BufReader::new(stderr).lines();
             while let Ok(Some(line)) = reader.next_line().await {
-                let _ = tx3.send(AppEvent::ActionOutput(line)).await;
+                let _ = tx3.send(AppEvent::ActionOutput { job, line }).await;
             }
-        });
+        }));
+    }
+
+    let (status, cancelled) = tokio::select! {
+        status = child.wait() => (status, false),
+        Ok(()) = &mut cancel => (terminate(&mut child).await, true),
+    };
+    // Drain remaining output before reporting completion.
+    for reader in readers {
+        let _ = reader.await;
+    }
+    if cancelled {
+        let _ = tx.send(AppEvent::ActionCancelled { job }).await;
+    } else {
+        let exit_code = status.ok().and_then(|s| s.code());
+        let _ = tx.send(AppEvent::ActionDone { job, exit_code }).await;
     }
+}
 
-    let status = child.wait().await;
-    let success = status.map(|s| s.success()).unwrap_or(false);
-    let _ = tx.send(AppEvent::ActionDone(success)).await;
+/// Stops a running brew process group: SIGINT first so brew can clean up,
+/// then SIGTERM, then SIGKILL, waiting `CANCEL_GRACE` between each.
+async fn terminate(child: &mut Child) -> std::io::Result<ExitStatus> {
+    if let Some(pid) = child.id() {
+        for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGKILL] {
+            // SAFETY: kill(2) has no memory-safety preconditions; a negative
+            // pid targets the process group created for this child.
+            unsafe {
+                libc::kill(-(pid as libc::pid_t), signal);
+            }
+            if let Ok(status) = tokio::time::timeout(CANCEL_GRACE, child.wait()).await {
+                return status;
+            }
+        }
+    }
+    child.kill().await?;
+    child.wait().await
 }
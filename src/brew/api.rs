@@ -1,24 +1,228 @@
-use reqwest::Client;
-use crate::brew::types::{CaskInfo, FormulaInfo};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
 
-pub async fn fetch_formulae(client: &Client) -> Result<Vec<FormulaInfo>, String> {
-    client
-        .get("https://formulae.brew.sh/api/formula.json")
-        .send()
-        .await
-        .map_err(|e| e.to_string())?
-        .json::<Vec<FormulaInfo>>()
-        .await
-        .map_err(|e| e.to_string())
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::brew::cache::{self, CacheMeta, CachedBody};
+use crate::brew::types::{CaskInfo, CatalogSource, FormulaInfo, PackageCounts};
+use crate::config::DEFAULT_API_DOMAIN;
+
+/// An HTTP client that gives up quickly when offline or when a download
+/// stalls, so the cached catalog can be served instead of leaving the
+/// Browse tabs loading.
+pub fn client() -> Client {
+    Client::builder()
+        .connect_timeout(Duration::from_secs(10))
+        .read_timeout(Duration::from_secs(30))
+        .build()
+        .unwrap_or_default()
 }
 
-pub async fn fetch_casks(client: &Client) -> Result<Vec<CaskInfo>, String> {
-    client
-        .get("https://formulae.brew.sh/api/cask.json")
-        .send()
-        .await
-        .map_err(|e| e.to_string())?
-        .json::<Vec<CaskInfo>>()
+/// The on-disk copy of the formula catalog, shown while `fetch_formulae`
+/// revalidates it.
+pub fn cached_formulae(api_domain: &str) -> Option<Vec<FormulaInfo>> {
+    parse(&cache::read(&cache_name(api_domain, "formula.json"))?.body).ok()
+}
+
+/// The on-disk copy of the cask catalog; see `cached_formulae`.
+pub fn cached_casks(api_domain: &str) -> Option<Vec<CaskInfo>> {
+    parse(&cache::read(&cache_name(api_domain, "cask.json"))?.body).ok()
+}
+
+/// Loads the formula catalog from `api_domain`. When `use_homebrew_cache` is
+/// set, Homebrew's own copy of the catalog is preferred over HTTP.
+pub async fn fetch_formulae(
+    client: &Client,
+    api_domain: &str,
+    use_homebrew_cache: bool,
+) -> Result<(Vec<FormulaInfo>, CatalogSource), String> {
+    if use_homebrew_cache {
+        if let Some(data) = read_homebrew_catalog("formula.jws.json").await {
+            return Ok((data, CatalogSource::Homebrew));
+        }
+    }
+    let url = format!("{api_domain}/formula.json");
+    fetch_catalog(client, &url, &cache_name(api_domain, "formula.json")).await
+}
+
+/// Loads the cask catalog; see `fetch_formulae`.
+pub async fn fetch_casks(
+    client: &Client,
+    api_domain: &str,
+    use_homebrew_cache: bool,
+) -> Result<(Vec<CaskInfo>, CatalogSource), String> {
+    if use_homebrew_cache {
+        if let Some(data) = read_homebrew_catalog("cask.jws.json").await {
+            return Ok((data, CatalogSource::Homebrew));
+        }
+    }
+    let url = format!("{api_domain}/cask.json");
+    fetch_catalog(client, &url, &cache_name(api_domain, "cask.json")).await
+}
+
+/// One entry of an analytics report. Formula reports name the package
+/// `formula`, cask reports `cask`; counts are formatted like `"12,345"`.
+#[derive(Deserialize)]
+struct AnalyticsItem {
+    #[serde(alias = "formula", alias = "cask")]
+    name: String,
+    count: String,
+}
+
+#[derive(Deserialize)]
+struct AnalyticsReport {
+    #[serde(default)]
+    items: Vec<AnalyticsItem>,
+}
+
+/// Loads 30-day install counts for formulae and casks from Homebrew's
+/// analytics, cached like the catalogs.
+pub async fn fetch_popularity(client: &Client, api_domain: &str) -> Result<PackageCounts, String> {
+    let formulae = fetch_analytics(client, api_domain, "install").await?;
+    let casks = fetch_analytics(client, api_domain, "cask-install").await?;
+    Ok(PackageCounts { formulae, casks })
+}
+
+async fn fetch_analytics(
+    client: &Client,
+    api_domain: &str,
+    category: &str,
+) -> Result<HashMap<String, u64>, String> {
+    let url = format!("{api_domain}/analytics/{category}/30d.json");
+    let file = format!("analytics-{category}-30d.json");
+    let (report, _): (AnalyticsReport, _) = fetch_catalog(client, &url, &cache_name(api_domain, &file)).await?;
+    Ok(report
+        .items
+        .into_iter()
+        .filter_map(|item| {
+            let count = item.count.replace(',', "").parse().ok()?;
+            Some((item.name, count))
+        })
+        .collect())
+}
+
+/// Keeps catalogs from different API domains apart in the on-disk cache, so
+/// a stale fallback never serves another server's data.
+fn cache_name(api_domain: &str, file: &str) -> String {
+    if api_domain == DEFAULT_API_DOMAIN {
+        return file.to_string();
+    }
+    let prefix: String = api_domain
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{prefix}-{file}")
+}
+
+/// The signed envelope Homebrew stores its API downloads in. The payload is
+/// the catalog JSON, unencoded, as a string.
+#[derive(Deserialize)]
+struct Jws {
+    payload: String,
+}
+
+/// Homebrew's API download directory: `$HOMEBREW_CACHE/api`, or
+/// `$(brew --cache)/api`.
+async fn homebrew_api_cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("HOMEBREW_CACHE").filter(|v| !v.is_empty()) {
+        return Some(PathBuf::from(dir).join("api"));
+    }
+    let output = tokio::process::Command::new("brew")
+        .arg("--cache")
+        .output()
         .await
-        .map_err(|e| e.to_string())
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!dir.is_empty()).then(|| PathBuf::from(dir).join("api"))
+}
+
+/// Loads a catalog from Homebrew's own API cache so taphouse shows exactly
+/// what the local `brew` would install. Returns `None` if the file is
+/// missing or unreadable, in which case the caller falls back to HTTP.
+async fn read_homebrew_catalog<T: DeserializeOwned>(file: &str) -> Option<T> {
+    let path = homebrew_api_cache_dir().await?.join(file);
+    let bytes = tokio::fs::read(path).await.ok()?;
+    let jws: Jws = serde_json::from_slice(&bytes).ok()?;
+    serde_json::from_str(&jws.payload).ok()
+}
+
+/// Fetches a catalog, revalidating the on-disk copy with `If-None-Match` /
+/// `If-Modified-Since`. If the network is unavailable, or the download is
+/// cut short or garbled, the cached copy is served as `CatalogSource::Stale`.
+async fn fetch_catalog<T: DeserializeOwned>(
+    client: &Client,
+    url: &str,
+    cache_name: &str,
+) -> Result<(T, CatalogSource), String> {
+    let cached = cache::read(cache_name);
+
+    let mut req = client.get(url);
+    if let Some(c) = &cached {
+        if let Some(etag) = &c.meta.etag {
+            req = req.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &c.meta.last_modified {
+            req = req.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let resp = match req.send().await {
+        Ok(r) => r,
+        Err(e) => return serve_stale(cached, e.to_string()),
+    };
+
+    let status = resp.status();
+    if status == StatusCode::NOT_MODIFIED {
+        if let Some(c) = &cached {
+            return parse(&c.body).map(|data| (data, CatalogSource::Revalidated));
+        }
+    }
+    if !status.is_success() {
+        return serve_stale(cached, format!("{url} returned {status}"));
+    }
+
+    let header = |name| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+    };
+    let meta = CacheMeta {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    };
+
+    let body = match resp.bytes().await {
+        Ok(b) => b,
+        Err(e) => return serve_stale(cached, e.to_string()),
+    };
+    let data = match parse(&body) {
+        Ok(data) => data,
+        Err(e) => return serve_stale(cached, format!("{url} returned invalid JSON: {e}")),
+    };
+    // The cache is best effort: a read-only or full disk shouldn't stop
+    // the catalog from loading.
+    let _ = cache::write(cache_name, &body, &meta);
+    Ok((data, CatalogSource::Network))
+}
+
+fn serve_stale<T: DeserializeOwned>(
+    cached: Option<CachedBody>,
+    error: String,
+) -> Result<(T, CatalogSource), String> {
+    match cached {
+        Some(c) => parse(&c.body).map(|data| (data, CatalogSource::Stale)),
+        None => Err(error),
+    }
+}
+
+fn parse<T: DeserializeOwned>(body: &[u8]) -> Result<T, String> {
+    serde_json::from_slice(body).map_err(|e| e.to_string())
 }
@@ -0,0 +1,61 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Validators saved alongside a cached response body so the next request
+/// can be made conditional.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheMeta {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+pub struct CachedBody {
+    pub body: Vec<u8>,
+    pub meta: CacheMeta,
+}
+
+/// `$XDG_CACHE_HOME/taphouse`, falling back to `~/.cache/taphouse`.
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))?;
+    Some(base.join("taphouse"))
+}
+
+fn meta_path(body_path: &std::path::Path) -> PathBuf {
+    let mut name = body_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".meta");
+    body_path.with_file_name(name)
+}
+
+/// Reads a cached body and its validators. Missing or unreadable metadata
+/// just means the next request won't be conditional.
+pub fn read(name: &str) -> Option<CachedBody> {
+    let path = cache_dir()?.join(name);
+    let body = fs::read(&path).ok()?;
+    let meta = fs::read(meta_path(&path))
+        .ok()
+        .and_then(|m| serde_json::from_slice(&m).ok())
+        .unwrap_or_default();
+    Some(CachedBody { body, meta })
+}
+
+/// Stores a body and its validators, replacing the files atomically so a
+/// crash mid-write never leaves a truncated catalog behind.
+pub fn write(name: &str, body: &[u8], meta: &CacheMeta) -> Result<(), String> {
+    let dir = cache_dir().ok_or("No cache directory (HOME is not set)")?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+
+    let path = dir.join(name);
+    let meta_json = serde_json::to_vec(meta).map_err(|e| e.to_string())?;
+    for (target, contents) in [(path.clone(), body), (meta_path(&path), meta_json.as_slice())] {
+        let mut tmp = target.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, contents).map_err(|e| format!("Failed to write {}: {e}", target.display()))?;
+        fs::rename(&tmp, &target).map_err(|e| format!("Failed to write {}: {e}", target.display()))?;
+    }
+    Ok(())
+}
@@ -1,45 +1,120 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-pub fn list_formulae() -> Result<Vec<String>, String> {
+use serde::Deserialize;
+
+use crate::brew::types::{InstalledPackages, OutdatedPackage, PackageCounts, PackageKind};
+
+/// Lists installed formulae and casks with their install metadata via
+/// `brew info --json=v2 --installed`.
+pub fn list_installed() -> Result<InstalledPackages, String> {
     let output = Command::new("brew")
-        .args(["list", "--formula", "-1"])
+        .args(["info", "--json=v2", "--installed"])
         .output()
         .map_err(|e| format!("Failed to run brew: {e}"))?;
 
     if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout)
-            .lines()
-            .filter(|l| !l.is_empty())
-            .map(String::from)
-            .collect())
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Failed to parse brew info output: {e}"))
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
         Err(format!(
-            "brew list --formula failed ({}): {}",
+            "brew info --installed failed ({}): {}",
             output.status,
             stderr.trim()
         ))
     }
 }
 
-pub fn list_casks() -> Result<Vec<String>, String> {
+#[derive(Deserialize)]
+struct OutdatedV2 {
+    #[serde(default)]
+    formulae: Vec<OutdatedPackage>,
+    #[serde(default)]
+    casks: Vec<OutdatedPackage>,
+}
+
+/// Lists outdated formulae and casks via `brew outdated --json=v2`.
+pub fn list_outdated() -> Result<Vec<OutdatedPackage>, String> {
     let output = Command::new("brew")
-        .args(["list", "--cask", "-1"])
+        .args(["outdated", "--json=v2"])
         .output()
         .map_err(|e| format!("Failed to run brew: {e}"))?;
 
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout)
-            .lines()
-            .filter(|l| !l.is_empty())
-            .map(String::from)
-            .collect())
-    } else {
+    // brew may exit non-zero while still printing valid JSON, so only treat
+    // the status as fatal when there is nothing to parse.
+    if !output.status.success() && output.stdout.is_empty() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        Err(format!(
-            "brew list --cask failed ({}): {}",
+        return Err(format!(
+            "brew outdated failed ({}): {}",
             output.status,
             stderr.trim()
-        ))
+        ));
+    }
+
+    let parsed: OutdatedV2 = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse brew outdated output: {e}"))?;
+
+    let formulae = parsed.formulae.into_iter().map(|mut p| {
+        p.kind = PackageKind::Formula;
+        p
+    });
+    let casks = parsed.casks.into_iter().map(|mut p| {
+        p.kind = PackageKind::Cask;
+        p
+    });
+    Ok(formulae.chain(casks).collect())
+}
+
+/// Homebrew's install prefix: `$HOMEBREW_PREFIX`, or `brew --prefix`.
+fn brew_prefix() -> Result<PathBuf, String> {
+    if let Some(prefix) = std::env::var_os("HOMEBREW_PREFIX").filter(|v| !v.is_empty()) {
+        return Ok(PathBuf::from(prefix));
+    }
+    let output = Command::new("brew")
+        .arg("--prefix")
+        .output()
+        .map_err(|e| format!("Failed to run brew: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        return Err(format!("brew --prefix failed ({}): {}", output.status, stderr.trim()));
+    }
+    Ok(PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+/// Measures how much disk space each installed formula (all kegs in the
+/// Cellar) and cask (its Caskroom directory) takes up. Apps that a cask
+/// moved to /Applications are not counted.
+pub fn disk_usage() -> Result<PackageCounts, String> {
+    let prefix = brew_prefix()?;
+    Ok(PackageCounts {
+        formulae: sizes_in(&prefix.join("Cellar")),
+        casks: sizes_in(&prefix.join("Caskroom")),
+    })
+}
+
+/// The total size of each subdirectory of `dir`, keyed by its name.
+fn sizes_in(dir: &Path) -> HashMap<String, u64> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .map(|e| (e.file_name().to_string_lossy().to_string(), dir_size(&e.path())))
+        .collect()
+}
+
+/// Sums file sizes under `path` without following symlinks.
+fn dir_size(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
     }
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries.flatten().map(|e| dir_size(&e.path())).sum()
 }
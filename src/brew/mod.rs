@@ -1,4 +1,7 @@
 pub mod actions;
 pub mod api;
+pub mod cache;
 pub mod installed;
+pub mod services;
+pub mod taps;
 pub mod types;
@@ -0,0 +1,26 @@
+use std::process::Command;
+
+use crate::brew::types::ServiceInfo;
+
+/// Lists services managed by `brew services list --json`.
+pub fn list_services() -> Result<Vec<ServiceInfo>, String> {
+    let output = Command::new("brew")
+        .args(["services", "list", "--json"])
+        .output()
+        .map_err(|e| format!("Failed to run brew: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        return Err(format!(
+            "brew services list failed ({}): {}",
+            output.status,
+            stderr.trim()
+        ));
+    }
+    // With no services brew prints nothing at all rather than `[]`.
+    if output.stdout.iter().all(u8::is_ascii_whitespace) {
+        return Ok(vec![]);
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse brew services output: {e}"))
+}
@@ -0,0 +1,94 @@
+use std::process::Command;
+
+use serde::Deserialize;
+
+use crate::brew::types::{CaskInfo, FormulaInfo, TapInfo};
+
+/// Taps whose contents the API catalogs already cover.
+pub const CATALOG_TAPS: [&str; 2] = ["homebrew/core", "homebrew/cask"];
+
+/// Lists tapped repositories via `brew tap-info --json --installed`.
+pub fn list_taps() -> Result<Vec<TapInfo>, String> {
+    let output = Command::new("brew")
+        .args(["tap-info", "--json", "--installed"])
+        .output()
+        .map_err(|e| format!("Failed to run brew: {e}"))?;
+
+    if output.status.success() {
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Failed to parse brew tap-info output: {e}"))
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        Err(format!(
+            "brew tap-info failed ({}): {}",
+            output.status,
+            stderr.trim()
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+struct InfoV2 {
+    #[serde(default)]
+    formulae: Vec<FormulaInfo>,
+    #[serde(default)]
+    casks: Vec<CaskInfo>,
+}
+
+/// Formulae from locally tapped third-party taps, which the API catalog
+/// doesn't publish.
+pub fn tap_formulae() -> Result<Vec<FormulaInfo>, String> {
+    let names: Vec<String> = third_party_taps()?
+        .into_iter()
+        .flat_map(|t| t.formula_names)
+        .collect();
+    let mut formulae = info(&names, "--formula")?.formulae;
+    for f in &mut formulae {
+        f.local_tap = true;
+    }
+    Ok(formulae)
+}
+
+/// Casks from locally tapped third-party taps; see `tap_formulae`.
+pub fn tap_casks() -> Result<Vec<CaskInfo>, String> {
+    let names: Vec<String> = third_party_taps()?
+        .into_iter()
+        .flat_map(|t| t.cask_tokens)
+        .collect();
+    let mut casks = info(&names, "--cask")?.casks;
+    for c in &mut casks {
+        c.local_tap = true;
+    }
+    Ok(casks)
+}
+
+fn third_party_taps() -> Result<Vec<TapInfo>, String> {
+    let mut taps = list_taps()?;
+    taps.retain(|t| !CATALOG_TAPS.contains(&t.name.as_str()));
+    Ok(taps)
+}
+
+/// Runs `brew info --json=v2 <kind_flag> <names...>`. Names are fully
+/// qualified (`user/tap/name`), so they can't resolve to a core formula.
+fn info(names: &[String], kind_flag: &str) -> Result<InfoV2, String> {
+    if names.is_empty() {
+        return Ok(InfoV2 { formulae: vec![], casks: vec![] });
+    }
+    let output = Command::new("brew")
+        .args(["info", "--json=v2", kind_flag])
+        .args(names)
+        .output()
+        .map_err(|e| format!("Failed to run brew: {e}"))?;
+
+    if output.status.success() {
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Failed to parse brew info output: {e}"))
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        Err(format!(
+            "brew info {kind_flag} failed ({}): {}",
+            output.status,
+            stderr.trim()
+        ))
+    }
+}
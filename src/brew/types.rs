@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::Deserialize;
 
 /// Deserializes a JSON `null` as `T::default()` instead of failing.
@@ -32,6 +34,15 @@ pub struct FormulaInfo {
     pub dependencies: Vec<String>,
     #[serde(default)]
     pub tap: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub oldnames: Vec<String>,
+    #[serde(default)]
+    pub deprecated: bool,
+    /// Loaded from a locally tapped third-party tap rather than the API.
+    #[serde(skip)]
+    pub local_tap: bool,
 }
 
 #[allow(dead_code)]
@@ -47,14 +58,233 @@ pub struct CaskInfo {
     #[serde(default)]
     pub version: String,
     pub auto_updates: Option<bool>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub tap: String,
+    #[serde(default)]
+    pub old_tokens: Vec<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub depends_on: CaskDependsOn,
+    #[serde(default)]
+    pub deprecated: bool,
+    /// Loaded from a locally tapped third-party tap rather than the API.
+    #[serde(skip)]
+    pub local_tap: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuntimeDependency {
+    pub full_name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub declared_directly: bool,
+}
+
+/// One installed version (keg) of a formula, taken from its install receipt.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstalledKeg {
+    pub version: String,
+    #[serde(default)]
+    pub poured_from_bottle: bool,
+    pub time: Option<i64>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub runtime_dependencies: Vec<RuntimeDependency>,
+    #[serde(default)]
+    pub installed_as_dependency: bool,
+    #[serde(default)]
+    pub installed_on_request: bool,
+}
+
+/// A formula entry from `brew info --json=v2 --installed`.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstalledFormula {
+    pub name: String,
+    pub full_name: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub desc: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub tap: String,
+    pub license: Option<String>,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub oldnames: Vec<String>,
+    #[serde(default)]
+    pub deprecated: bool,
+    #[serde(default)]
+    pub installed: Vec<InstalledKeg>,
+    pub linked_keg: Option<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub keg_only: bool,
+    #[serde(default)]
+    pub outdated: bool,
+}
+
+impl InstalledFormula {
+    pub fn installed_versions(&self) -> Vec<&str> {
+        self.installed.iter().map(|k| k.version.as_str()).collect()
+    }
+
+    /// The most recently installed keg, which is the one brew links by default.
+    pub fn latest_keg(&self) -> Option<&InstalledKeg> {
+        self.installed.iter().max_by_key(|k| k.time.unwrap_or(0))
+    }
+
+    pub fn installed_on_request(&self) -> bool {
+        self.installed.iter().any(|k| k.installed_on_request)
+    }
+
+    /// Installed only to satisfy another formula, per the install receipts.
+    pub fn is_dependency_install(&self) -> bool {
+        !self.installed_on_request() && self.installed.iter().any(|k| k.installed_as_dependency)
+    }
+
+    pub fn install_time(&self) -> Option<i64> {
+        self.latest_keg().and_then(|k| k.time)
+    }
+}
+
+/// A cask entry from `brew info --json=v2 --installed`.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstalledCask {
+    pub token: String,
+    #[serde(default)]
+    pub full_token: String,
+    #[serde(default)]
+    pub name: Vec<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub desc: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub tap: String,
+    #[serde(default)]
+    pub version: String,
+    pub installed: Option<String>,
+    pub installed_time: Option<i64>,
+    #[serde(default)]
+    pub outdated: bool,
+    pub auto_updates: Option<bool>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub depends_on: CaskDependsOn,
+    #[serde(default)]
+    pub deprecated: bool,
+}
+
+/// The package part of a cask's `depends_on`; macOS and architecture
+/// requirements are ignored.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CaskDependsOn {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub formula: Vec<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub cask: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstalledPackages {
+    #[serde(default)]
+    pub formulae: Vec<InstalledFormula>,
+    #[serde(default)]
+    pub casks: Vec<InstalledCask>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum PackageKind {
+    #[default]
     Formula,
     Cask,
 }
 
+/// An entry from `brew outdated --json=v2`. Formulae and casks share the
+/// same shape; `kind` is filled in after parsing.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct OutdatedPackage {
+    pub name: String,
+    #[serde(default)]
+    pub installed_versions: Vec<String>,
+    #[serde(default)]
+    pub current_version: String,
+    #[serde(default)]
+    pub pinned: bool,
+    pub pinned_version: Option<String>,
+    #[serde(skip)]
+    pub kind: PackageKind,
+}
+
+/// A tapped repository from `brew tap-info --json --installed`.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct TapInfo {
+    pub name: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub path: String,
+    pub remote: Option<String>,
+    #[serde(default)]
+    pub custom_remote: bool,
+    #[serde(default)]
+    pub official: bool,
+    #[serde(default)]
+    pub private: bool,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub formula_names: Vec<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub cask_tokens: Vec<String>,
+}
+
+/// A service from `brew services list --json`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServiceInfo {
+    pub name: String,
+    /// `started`, `stopped`, `none`, `scheduled`, `error`, ...
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub status: String,
+    pub user: Option<String>,
+    /// The launchd plist or systemd unit file.
+    pub file: Option<String>,
+    pub exit_code: Option<i64>,
+    pub pid: Option<i64>,
+}
+
+/// A number per package, keyed by kind and name: bytes on disk, or install
+/// counts from Homebrew's analytics.
+#[derive(Debug, Clone, Default)]
+pub struct PackageCounts {
+    pub formulae: HashMap<String, u64>,
+    pub casks: HashMap<String, u64>,
+}
+
+impl PackageCounts {
+    pub fn get(&self, kind: PackageKind, name: &str) -> Option<u64> {
+        match kind {
+            PackageKind::Formula => self.formulae.get(name).copied(),
+            PackageKind::Cask => self.casks.get(name).copied(),
+        }
+    }
+}
+
+/// Where a browse catalog was loaded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogSource {
+    /// Freshly downloaded.
+    Network,
+    /// The cached copy, confirmed current by the server.
+    Revalidated,
+    /// The cached copy, shown while the server is asked whether it's current.
+    Cached,
+    /// The cached copy, served because the network was unavailable.
+    Stale,
+    /// Homebrew's own API cache, as used by the local `brew`.
+    Homebrew,
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub enum RemoteData<T> {
@@ -0,0 +1,728 @@
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::brew::taps::CATALOG_TAPS;
+use crate::brew::types::{InstalledCask, InstalledFormula, InstalledPackages, TapInfo};
+use crate::deps::DepGraph;
+
+/// Extra comments written next to the package lines of an exported Brewfile.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExportOptions {
+    /// A `# description` line above each formula and cask.
+    pub describe: bool,
+    /// The installed version after each formula and cask, and whether a
+    /// formula is pinned.
+    pub versions: bool,
+}
+
+/// Builds a Brewfile from the installed state, the way `brew bundle dump`
+/// would: every tap but the built-in ones, formulae installed on request,
+/// and every cask, each group sorted by name.
+pub fn render(taps: &[TapInfo], installed: &InstalledPackages, options: ExportOptions) -> String {
+    let mut out = String::new();
+
+    let mut taps: Vec<&TapInfo> = taps
+        .iter()
+        .filter(|t| !CATALOG_TAPS.contains(&t.name.as_str()))
+        .collect();
+    taps.sort_by(|a, b| a.name.cmp(&b.name));
+    for tap in taps {
+        match tap.remote.as_deref() {
+            Some(remote) if tap.custom_remote => {
+                let _ = writeln!(out, "tap \"{}\", \"{remote}\"", tap.name);
+            }
+            _ => {
+                let _ = writeln!(out, "tap \"{}\"", tap.name);
+            }
+        }
+    }
+
+    // Formulae outside homebrew/core need their tap prefix to resolve.
+    let mut formulae: Vec<(&str, _)> = installed
+        .formulae
+        .iter()
+        .filter(|f| !f.is_dependency_install())
+        .map(|f| {
+            let name = if f.tap == "homebrew/core" || f.tap.is_empty() { &f.name } else { &f.full_name };
+            (name.as_str(), f)
+        })
+        .collect();
+    formulae.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, f) in formulae {
+        if options.describe && !f.desc.is_empty() {
+            let _ = writeln!(out, "# {}", f.desc);
+        }
+        let _ = write!(out, "brew \"{name}\"");
+        if options.versions {
+            if let Some(keg) = f.latest_keg() {
+                let pinned = if f.pinned { ", pinned" } else { "" };
+                let _ = write!(out, " # {}{pinned}", keg.version);
+            }
+        }
+        out.push('\n');
+    }
+
+    let mut casks: Vec<(&str, _)> = installed
+        .casks
+        .iter()
+        .map(|c| {
+            let token = if c.full_token.is_empty() { &c.token } else { &c.full_token };
+            (token.as_str(), c)
+        })
+        .collect();
+    casks.sort_by(|a, b| a.0.cmp(b.0));
+    for (token, c) in casks {
+        if options.describe && !c.desc.is_empty() {
+            let _ = writeln!(out, "# {}", c.desc);
+        }
+        let _ = write!(out, "cask \"{token}\"");
+        if options.versions {
+            let _ = write!(out, " # {}", c.installed.as_deref().unwrap_or(&c.version));
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Reads the installed state from brew and writes it as a Brewfile to
+/// `path`, or to stdout for `-`. Returns the number of entries written.
+pub fn export(path: &Path, options: ExportOptions, force: bool) -> Result<usize, String> {
+    if path != Path::new("-") && !force && path.exists() {
+        return Err(format!("{} already exists", path.display()));
+    }
+    let installed = crate::brew::installed::list_installed()?;
+    let taps = crate::brew::taps::list_taps()?;
+    let text = render(&taps, &installed, options);
+    let entries = text.lines().filter(|l| !l.starts_with('#')).count();
+    if path == Path::new("-") {
+        print!("{text}");
+    } else {
+        std::fs::write(path, text).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    }
+    Ok(entries)
+}
+
+/// A `tap`, `brew` or `cask` line of a Brewfile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Tap,
+    Brew,
+    Cask,
+}
+
+impl EntryKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EntryKind::Tap => "tap",
+            EntryKind::Brew => "brew",
+            EntryKind::Cask => "cask",
+        }
+    }
+}
+
+/// A formula's `restart_service:` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RestartService {
+    #[default]
+    Never,
+    /// `true`: restart whether or not the formula changed.
+    Always,
+    /// `:changed`: restart only after installing it.
+    Changed,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub kind: EntryKind,
+    pub name: String,
+    /// A tap's clone URL.
+    pub url: Option<String>,
+    /// Extra `brew install` flags from `args:`, e.g. `--HEAD` or
+    /// `--appdir=~/Applications`.
+    pub args: Vec<String>,
+    pub restart_service: RestartService,
+    /// Guarded by `if`/`unless`, on the line or around it. taphouse can't
+    /// evaluate the condition, so the entry is neither installed nor, on a
+    /// sync, removed.
+    pub conditional: bool,
+}
+
+/// A parsed Brewfile.
+#[derive(Debug, Clone)]
+pub struct Brewfile {
+    pub path: PathBuf,
+    pub entries: Vec<Entry>,
+    /// Directives taphouse doesn't manage, e.g. `mas` or `vscode`, and
+    /// lines with an `if`/`unless` condition.
+    pub ignored: Vec<String>,
+}
+
+impl Brewfile {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        let (entries, ignored) = parse(&text).map_err(|e| format!("{}: {e}", path.display()))?;
+        Ok(Brewfile { path: path.to_path_buf(), entries, ignored })
+    }
+}
+
+/// A Ruby literal, as far as Brewfiles use them. Symbols and barewords
+/// become strings.
+#[derive(Debug, Clone)]
+enum Value {
+    Str(String),
+    Bool(bool),
+    Nil,
+    Array(Vec<Value>),
+    Hash(Vec<(String, Value)>),
+}
+
+impl Value {
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Str(String),
+    Word(String),
+    /// `name:` in a keyword argument or hash.
+    Key(String),
+    Arrow,
+    Punct(char),
+}
+
+/// Parses the `tap`, `brew`, `cask` and `cask_args` lines of a Brewfile,
+/// returning the entries and what was skipped: other directives by name,
+/// and conditional lines in full. Conditional entries, from a trailing
+/// `if`/`unless` or an `if ... end` block, are returned too, marked as
+/// such. A statement continues onto the next line while brackets are open
+/// or it ends in a comma, as in Ruby.
+pub fn parse(text: &str) -> Result<(Vec<Entry>, Vec<String>), String> {
+    let mut entries = vec![];
+    let mut ignored = vec![];
+    let mut cask_args: Vec<String> = vec![];
+    let mut tokens: Vec<Token> = vec![];
+    let mut start = 0;
+    // Open `if`/`unless` blocks.
+    let mut depth = 0usize;
+    for (i, line) in text.lines().enumerate() {
+        if tokens.is_empty() {
+            start = i + 1;
+        }
+        tokens.extend(tokenize(line).map_err(|e| format!("line {}: {e}", i + 1))?);
+        if tokens.is_empty() || continues(&tokens) {
+            continue;
+        }
+        let statement = std::mem::take(&mut tokens);
+        let Statement { directive, positional, options, conditional } =
+            statement_parts(&statement).map_err(|e| format!("line {start}: {e}"))?;
+        match directive.as_str() {
+            "if" | "unless" => {
+                depth += 1;
+                continue;
+            }
+            "elsif" | "else" => continue,
+            "end" => {
+                depth = depth.checked_sub(1).ok_or_else(|| format!("line {start}: end without if"))?;
+                continue;
+            }
+            _ => {}
+        }
+        let conditional = conditional || depth > 0;
+        let name = positional.first().and_then(Value::as_str).map(str::to_string);
+        if conditional {
+            // Not installed rather than guessed: the condition may exclude
+            // this machine.
+            let what = match &name {
+                Some(name) => format!("{directive} \"{name}\" (conditional)"),
+                None => format!("{directive} (conditional)"),
+            };
+            ignored.push(what);
+        }
+        let kind = match directive.as_str() {
+            "tap" => EntryKind::Tap,
+            "brew" => EntryKind::Brew,
+            "cask" => EntryKind::Cask,
+            _ if conditional => continue,
+            "cask_args" => {
+                cask_args.extend(options.iter().filter_map(|(k, v)| cask_flag(k, v)));
+                continue;
+            }
+            _ => {
+                if !ignored.contains(&directive) {
+                    ignored.push(directive);
+                }
+                continue;
+            }
+        };
+        let name = name.ok_or_else(|| format!("line {start}: {directive} needs a name"))?;
+        let mut entry = Entry {
+            kind,
+            name,
+            url: positional.get(1).and_then(Value::as_str).map(str::to_string),
+            args: vec![],
+            restart_service: RestartService::Never,
+            conditional,
+        };
+        for (key, value) in &options {
+            match (kind, key.as_str(), value) {
+                (EntryKind::Tap, "clone_target", Value::Str(url)) => entry.url = Some(url.clone()),
+                (EntryKind::Brew, "args", Value::Array(args)) => {
+                    entry.args = args
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(|a| if a.starts_with('-') { a.to_string() } else { format!("--{a}") })
+                        .collect();
+                }
+                (EntryKind::Cask, "args", Value::Hash(args)) => {
+                    entry.args = args.iter().filter_map(|(k, v)| cask_flag(k, v)).collect();
+                }
+                (EntryKind::Brew, "restart_service", Value::Bool(true)) => {
+                    entry.restart_service = RestartService::Always;
+                }
+                (EntryKind::Brew, "restart_service", Value::Str(s)) if s == "changed" => {
+                    entry.restart_service = RestartService::Changed;
+                }
+                _ => {}
+            }
+        }
+        entries.push(entry);
+    }
+    if !tokens.is_empty() {
+        return Err(format!("line {start}: unterminated statement"));
+    }
+    if depth > 0 {
+        return Err("if without end".to_string());
+    }
+    for entry in entries.iter_mut().filter(|e| e.kind == EntryKind::Cask) {
+        // Per-cask args override the global ones.
+        let mut args: Vec<String> = cask_args
+            .iter()
+            .filter(|g| !entry.args.iter().any(|o| flag_name(o) == flag_name(g)))
+            .cloned()
+            .collect();
+        args.append(&mut entry.args);
+        entry.args = args;
+    }
+    Ok((entries, ignored))
+}
+
+/// `appdir: "~/Applications"` becomes `--appdir=~/Applications`, and
+/// `require_sha: true` becomes `--require-sha`.
+fn cask_flag(key: &str, value: &Value) -> Option<String> {
+    let key = key.replace('_', "-");
+    match value {
+        Value::Bool(true) => Some(format!("--{key}")),
+        Value::Str(s) => Some(format!("--{key}={s}")),
+        _ => None,
+    }
+}
+
+fn flag_name(flag: &str) -> &str {
+    flag.split('=').next().unwrap_or(flag)
+}
+
+fn continues(tokens: &[Token]) -> bool {
+    let depth = tokens.iter().fold(0i32, |depth, t| match t {
+        Token::Punct('[' | '{' | '(') => depth + 1,
+        Token::Punct(']' | '}' | ')') => depth - 1,
+        _ => depth,
+    });
+    depth > 0 || matches!(tokens.last(), Some(Token::Punct(',')) | Some(Token::Arrow) | Some(Token::Key(_)))
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '#' => break,
+            '"' | '\'' => {
+                chars.next();
+                tokens.push(Token::Str(read_string(&mut chars, c)?));
+            }
+            ':' => {
+                chars.next();
+                match chars.peek() {
+                    Some(&q @ ('"' | '\'')) => {
+                        chars.next();
+                        tokens.push(Token::Str(read_string(&mut chars, q)?));
+                    }
+                    _ => tokens.push(Token::Str(read_word(&mut chars))),
+                }
+            }
+            '=' => {
+                chars.next();
+                if chars.next() != Some('>') {
+                    return Err("expected =>".to_string());
+                }
+                tokens.push(Token::Arrow);
+            }
+            ',' | '[' | ']' | '{' | '}' | '(' | ')' => {
+                chars.next();
+                tokens.push(Token::Punct(c));
+            }
+            c if is_word_char(c) => {
+                let word = read_word(&mut chars);
+                // `key:` but not `Foo::Bar`.
+                let mut lookahead = chars.clone();
+                if lookahead.next() == Some(':') && lookahead.next() != Some(':') {
+                    chars.next();
+                    tokens.push(Token::Key(word));
+                } else if is_modifier(&word) || word == "elsif" {
+                    // The condition is arbitrary Ruby; only the keyword matters.
+                    tokens.push(Token::Word(word));
+                    break;
+                } else {
+                    tokens.push(Token::Word(word));
+                }
+            }
+            other => return Err(format!("unexpected {other:?}")),
+        }
+    }
+    Ok(tokens)
+}
+
+/// A trailing `if` or `unless` that makes a statement conditional.
+fn is_modifier(word: &str) -> bool {
+    matches!(word, "if" | "unless")
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '?' | '!' | '.' | '@' | '/')
+}
+
+fn read_word(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut word = String::new();
+    while let Some(&c) = chars.peek() {
+        if !is_word_char(c) {
+            break;
+        }
+        word.push(c);
+        chars.next();
+    }
+    word
+}
+
+fn read_string(chars: &mut std::iter::Peekable<std::str::Chars>, quote: char) -> Result<String, String> {
+    let mut s = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => s.extend(chars.next()),
+            c if c == quote => return Ok(s),
+            c => s.push(c),
+        }
+    }
+    Err("unterminated string".to_string())
+}
+
+/// A statement split into its directive, positional arguments and keyword
+/// options.
+struct Statement {
+    directive: String,
+    positional: Vec<Value>,
+    options: Vec<(String, Value)>,
+    /// Followed by `if ...` or `unless ...`, which taphouse can't evaluate.
+    conditional: bool,
+}
+
+fn statement_parts(tokens: &[Token]) -> Result<Statement, String> {
+    let Some(Token::Word(directive)) = tokens.first() else {
+        return Err("expected a directive".to_string());
+    };
+    let mut rest = &tokens[1..];
+    let conditional = matches!(rest.last(), Some(Token::Word(w)) if is_modifier(w));
+    if conditional {
+        rest = &rest[..rest.len() - 1];
+    }
+    if let (Some(Token::Punct('(')), Some(Token::Punct(')'))) = (rest.first(), rest.last()) {
+        rest = &rest[1..rest.len() - 1];
+    }
+    let mut parser = Parser { tokens: rest, pos: 0 };
+    let mut positional = vec![];
+    let mut options = vec![];
+    while !parser.done() {
+        if let Some(pair) = parser.pair()? {
+            options.push(pair);
+        } else {
+            positional.push(parser.value()?);
+        }
+        if !parser.done() {
+            parser.expect(',')?;
+        }
+    }
+    Ok(Statement { directive: directive.clone(), positional, options, conditional })
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn done(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, punct: char) -> Result<(), String> {
+        match self.next() {
+            Some(Token::Punct(c)) if *c == punct => Ok(()),
+            other => Err(format!("expected {punct:?}, found {other:?}")),
+        }
+    }
+
+    /// A `key: value` or `key => value` pair, if one starts here.
+    fn pair(&mut self) -> Result<Option<(String, Value)>, String> {
+        match (self.tokens.get(self.pos), self.tokens.get(self.pos + 1)) {
+            (Some(Token::Key(key)), _) => {
+                let key = key.clone();
+                self.pos += 1;
+                Ok(Some((key, self.value()?)))
+            }
+            (Some(Token::Str(key) | Token::Word(key)), Some(Token::Arrow)) => {
+                let key = key.clone();
+                self.pos += 2;
+                Ok(Some((key, self.value()?)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.next().cloned() {
+            Some(Token::Str(s)) => Ok(Value::Str(s)),
+            Some(Token::Word(w)) => Ok(match w.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "nil" => Value::Nil,
+                _ => Value::Str(w),
+            }),
+            Some(Token::Punct('[')) => {
+                let mut items = vec![];
+                while self.tokens.get(self.pos) != Some(&Token::Punct(']')) {
+                    items.push(self.value()?);
+                    if self.tokens.get(self.pos) != Some(&Token::Punct(']')) {
+                        self.expect(',')?;
+                    }
+                }
+                self.pos += 1;
+                Ok(Value::Array(items))
+            }
+            Some(Token::Punct('{')) => {
+                let mut pairs = vec![];
+                while self.tokens.get(self.pos) != Some(&Token::Punct('}')) {
+                    pairs.push(self.pair()?.ok_or("expected key: value")?);
+                    if self.tokens.get(self.pos) != Some(&Token::Punct('}')) {
+                        self.expect(',')?;
+                    }
+                }
+                self.pos += 1;
+                Ok(Value::Hash(pairs))
+            }
+            other => Err(format!("expected a value, found {other:?}")),
+        }
+    }
+}
+
+/// What loading a Brewfile would change.
+#[derive(Debug, Default)]
+pub struct Plan<'a> {
+    pub install: Vec<&'a Entry>,
+    pub satisfied: Vec<&'a Entry>,
+    /// Installed on request but not in the Brewfile, as `brew bundle
+    /// cleanup` would see it. Formulae the Brewfile's formulae depend on are
+    /// kept, and so is anything a conditional entry names.
+    pub remove: Vec<(EntryKind, String)>,
+}
+
+/// Diffs `entries` against the installed state. Conditional entries are
+/// only protected from removal.
+pub fn plan<'a>(entries: &'a [Entry], formulae: &[InstalledFormula], casks: &[InstalledCask], taps: &[TapInfo]) -> Plan<'a> {
+    let mut plan = Plan::default();
+    let mut wanted_formulae: HashSet<&str> = HashSet::new();
+    let mut wanted_casks: HashSet<&str> = HashSet::new();
+    let mut wanted_taps: HashSet<String> = HashSet::new();
+
+    for entry in entries {
+        let installed = match entry.kind {
+            EntryKind::Tap => {
+                wanted_taps.insert(entry.name.to_lowercase());
+                taps.iter().any(|t| t.name.eq_ignore_ascii_case(&entry.name))
+            }
+            EntryKind::Brew => match formulae.iter().find(|f| formula_matches(f, &entry.name)) {
+                Some(f) => {
+                    wanted_formulae.insert(&f.name);
+                    true
+                }
+                None => false,
+            },
+            EntryKind::Cask => match casks.iter().find(|c| cask_matches(c, &entry.name)) {
+                Some(c) => {
+                    wanted_casks.insert(&c.token);
+                    true
+                }
+                None => false,
+            },
+        };
+        if entry.conditional {
+            // Protected from removal above; nothing to install.
+        } else if installed {
+            plan.satisfied.push(entry);
+        } else {
+            plan.install.push(entry);
+        }
+        // `user/repo/name` implies its tap.
+        if entry.kind != EntryKind::Tap {
+            if let Some((tap, _)) = entry.name.rsplit_once('/') {
+                wanted_taps.insert(tap.to_lowercase());
+            }
+        }
+    }
+
+    let graph = DepGraph::new(None, formulae);
+    let needed: HashSet<&str> = wanted_formulae
+        .iter()
+        .flat_map(|name| graph.closure(name))
+        .chain(wanted_formulae.iter().copied())
+        .collect();
+    let mut kept_taps: HashSet<String> = HashSet::new();
+    for f in formulae {
+        if f.is_dependency_install() || needed.contains(f.name.as_str()) {
+            kept_taps.insert(f.tap.to_lowercase());
+        } else {
+            plan.remove.push((EntryKind::Brew, f.name.clone()));
+        }
+    }
+    for c in casks {
+        if wanted_casks.contains(c.token.as_str()) {
+            kept_taps.insert(c.tap.to_lowercase());
+        } else {
+            plan.remove.push((EntryKind::Cask, c.token.clone()));
+        }
+    }
+    for tap in taps {
+        let name = tap.name.to_lowercase();
+        if !CATALOG_TAPS.contains(&name.as_str()) && !wanted_taps.contains(&name) && !kept_taps.contains(&name) {
+            plan.remove.push((EntryKind::Tap, tap.name.clone()));
+        }
+    }
+    plan
+}
+
+/// `user/repo/name` must match exactly; a bare name also matches aliases
+/// and old names.
+pub fn formula_matches(f: &InstalledFormula, name: &str) -> bool {
+    if name.contains('/') {
+        f.full_name.eq_ignore_ascii_case(name)
+    } else {
+        f.name == name || f.aliases.iter().any(|a| a == name) || f.oldnames.iter().any(|o| o == name)
+    }
+}
+
+pub fn cask_matches(c: &InstalledCask, name: &str) -> bool {
+    if name.contains('/') {
+        c.full_token.eq_ignore_ascii_case(name)
+    } else {
+        c.token == name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formula(name: &str) -> InstalledFormula {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "full_name": name,
+            "tap": "homebrew/core",
+            "installed": [{ "version": "1.0", "installed_on_request": true }],
+        }))
+        .unwrap()
+    }
+
+    fn cask(token: &str) -> InstalledCask {
+        serde_json::from_value(serde_json::json!({ "token": token, "full_token": token, "tap": "homebrew/cask" }))
+            .unwrap()
+    }
+
+    fn names(entries: &[Entry]) -> Vec<(&str, bool)> {
+        entries.iter().map(|e| (e.name.as_str(), e.conditional)).collect()
+    }
+
+    #[test]
+    fn parses_options_and_continuations() {
+        let text = r#"
+tap "user/repo", "https://example.com/repo.git"
+cask_args appdir: "~/Applications"
+brew "wget", args: ["HEAD"], restart_service: :changed
+cask "firefox",
+  args: { appdir: "/Applications", require_sha: true }
+mas "Xcode", id: 497799835
+"#;
+        let (entries, ignored) = parse(text).unwrap();
+        assert_eq!(names(&entries), [("user/repo", false), ("wget", false), ("firefox", false)]);
+        assert_eq!(entries[0].url.as_deref(), Some("https://example.com/repo.git"));
+        assert_eq!(entries[1].args, ["--HEAD"]);
+        assert_eq!(entries[1].restart_service, RestartService::Changed);
+        assert_eq!(entries[2].args, ["--appdir=/Applications", "--require-sha"]);
+        assert_eq!(ignored, ["mas"]);
+    }
+
+    #[test]
+    fn trailing_modifier_marks_entry_conditional() {
+        let text = "cask \"iterm2\" if OS.mac? && ENV[\"CI\"] == nil\nbrew \"jq\" unless OS.linux?\nbrew \"git\"\n";
+        let (entries, ignored) = parse(text).unwrap();
+        assert_eq!(names(&entries), [("iterm2", true), ("jq", true), ("git", false)]);
+        assert_eq!(ignored, ["cask \"iterm2\" (conditional)", "brew \"jq\" (conditional)"]);
+    }
+
+    #[test]
+    fn block_conditional_marks_entries_inside() {
+        let text = "if OS.linux?\n  brew \"gcc\"\nelsif OS.mac? && true\n  cask \"iterm2\"\nelse\n  brew \"zsh\"\nend\nbrew \"git\"\n";
+        let (entries, ignored) = parse(text).unwrap();
+        assert_eq!(names(&entries), [("gcc", true), ("iterm2", true), ("zsh", true), ("git", false)]);
+        assert!(!ignored.iter().any(|i| i == "if" || i == "end"));
+    }
+
+    #[test]
+    fn unbalanced_blocks_are_errors() {
+        assert!(parse("if OS.mac?\nbrew \"git\"\n").is_err());
+        assert!(parse("brew \"git\"\nend\n").is_err());
+    }
+
+    #[test]
+    fn sync_keeps_conditional_entries() {
+        let (entries, _) = parse("brew \"git\"\ncask \"iterm2\" if OS.mac?\nif OS.mac?\n  brew \"jq\"\nend\n").unwrap();
+        let formulae = [formula("git"), formula("jq"), formula("wget")];
+        let casks = [cask("iterm2"), cask("firefox")];
+        let plan = plan(&entries, &formulae, &casks, &[]);
+        assert_eq!(plan.remove, [(EntryKind::Brew, "wget".to_string()), (EntryKind::Cask, "firefox".to_string())]);
+        assert!(plan.install.is_empty());
+        assert_eq!(plan.satisfied.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(), ["git"]);
+    }
+
+    #[test]
+    fn plan_installs_missing_entries_and_their_taps() {
+        let (entries, _) = parse("brew \"user/repo/tool\"\nbrew \"git\"\n").unwrap();
+        let tap: TapInfo = serde_json::from_value(serde_json::json!({ "name": "user/repo" })).unwrap();
+        let plan = plan(&entries, &[formula("git")], &[], &[tap]);
+        assert_eq!(plan.install.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(), ["user/repo/tool"]);
+        assert!(plan.remove.is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+use std::path::PathBuf;
+
+use crate::brewfile::ExportOptions;
+use crate::license::ReportFormat;
+
+pub const USAGE: &str = "\
+Usage: taphouse [OPTIONS] [COMMAND]
+
+Commands:
+  export [PATH]           Write a Brewfile of the installed taps, formulae and casks
+                          to PATH (default: ./Brewfile, - for stdout)
+      --describe          Add package descriptions as comments
+      --versions          Add installed versions and pins as comments
+      --force             Overwrite an existing file
+  licenses [PATH]         Write a license report of the installed formulae to PATH
+                          (default: - for stdout)
+      --format <FORMAT>   json, csv or markdown (default: from PATH's extension,
+                          else markdown)
+
+Options:
+      --api-domain <URL>  Homebrew API base URL (default: https://formulae.brew.sh/api)
+      --brewfile <PATH>   Open the install plan for a Brewfile at startup
+      --manifest <PATH>   Team manifest to check in the Compliance tab
+      --config <PATH>     Config file (default: $XDG_CONFIG_HOME/taphouse/config.toml)
+      --debug             Enable the debug log panel (toggle with ?)
+  -h, --help              Print this help
+";
+
+/// A command run instead of the TUI.
+#[derive(Debug)]
+pub enum Command {
+    Export { path: PathBuf, options: ExportOptions, force: bool },
+    Licenses { path: PathBuf, format: Option<ReportFormat> },
+}
+
+/// Command-line arguments.
+#[derive(Debug, Default)]
+pub struct Cli {
+    pub debug: bool,
+    pub help: bool,
+    pub api_domain: Option<String>,
+    pub config_path: Option<PathBuf>,
+    pub brewfile: Option<PathBuf>,
+    pub manifest: Option<PathBuf>,
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    pub fn parse() -> Result<Self, String> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut cli = Cli::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // Accept both `--flag value` and `--flag=value`.
+            let (flag, inline) = match arg.split_once('=') {
+                Some((f, v)) if f.starts_with("--") => (f.to_string(), Some(v.to_string())),
+                _ => (arg, None),
+            };
+            let mut value = |name: &str| {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{name} requires a value"))
+            };
+            match flag.as_str() {
+                "--debug" => cli.debug = true,
+                "-h" | "--help" => cli.help = true,
+                "--api-domain" => cli.api_domain = Some(value("--api-domain")?),
+                "--config" => cli.config_path = Some(PathBuf::from(value("--config")?)),
+                "--brewfile" => cli.brewfile = Some(PathBuf::from(value("--brewfile")?)),
+                "--manifest" => cli.manifest = Some(PathBuf::from(value("--manifest")?)),
+                "export" if cli.command.is_none() => {
+                    cli.command = Some(Command::Export {
+                        path: PathBuf::from("Brewfile"),
+                        options: ExportOptions::default(),
+                        force: false,
+                    });
+                }
+                "licenses" if cli.command.is_none() => {
+                    cli.command = Some(Command::Licenses { path: PathBuf::from("-"), format: None });
+                }
+                "--format" => {
+                    let name = value("--format")?;
+                    match &mut cli.command {
+                        Some(Command::Licenses { format, .. }) => *format = Some(ReportFormat::parse(&name)?),
+                        _ => return Err(format!("--format is only valid with licenses\n\n{USAGE}")),
+                    }
+                }
+                "--describe" | "--versions" | "--force" => match &mut cli.command {
+                    Some(Command::Export { options, force, .. }) => match flag.as_str() {
+                        "--describe" => options.describe = true,
+                        "--versions" => options.versions = true,
+                        _ => *force = true,
+                    },
+                    _ => return Err(format!("{flag} is only valid with export\n\n{USAGE}")),
+                },
+                other if !other.starts_with('-') || other == "-" => match &mut cli.command {
+                    Some(Command::Export { path, .. } | Command::Licenses { path, .. }) => *path = PathBuf::from(other),
+                    None => return Err(format!("Unknown command: {other}\n\n{USAGE}")),
+                },
+                other => return Err(format!("Unknown argument: {other}\n\n{USAGE}")),
+            }
+        }
+        Ok(cli)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, String> {
+        Cli::parse_from(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn parses_options_in_both_forms() {
+        let cli = parse(&["--debug", "--brewfile", "Brewfile", "--manifest=team.toml", "--api-domain=http://localhost"]).unwrap();
+        assert!(cli.debug);
+        assert_eq!(cli.brewfile, Some(PathBuf::from("Brewfile")));
+        assert_eq!(cli.manifest, Some(PathBuf::from("team.toml")));
+        assert_eq!(cli.api_domain.as_deref(), Some("http://localhost"));
+        assert!(cli.command.is_none());
+        assert_eq!(parse(&["--config"]).unwrap_err(), "--config requires a value");
+    }
+
+    #[test]
+    fn parses_export_with_defaults_and_flags() {
+        let Some(Command::Export { path, options, force }) = parse(&["export"]).unwrap().command else {
+            panic!("expected export");
+        };
+        assert_eq!(path, PathBuf::from("Brewfile"));
+        assert!(!options.describe && !options.versions && !force);
+
+        let Some(Command::Export { path, options, force }) =
+            parse(&["export", "--describe", "-", "--versions", "--force"]).unwrap().command
+        else {
+            panic!("expected export");
+        };
+        assert_eq!(path, PathBuf::from("-"));
+        assert!(options.describe && options.versions && force);
+    }
+
+    #[test]
+    fn parses_licenses_with_format() {
+        let Some(Command::Licenses { path, format }) = parse(&["licenses"]).unwrap().command else {
+            panic!("expected licenses");
+        };
+        assert_eq!((path, format), (PathBuf::from("-"), None));
+
+        let Some(Command::Licenses { path, format }) =
+            parse(&["licenses", "report.txt", "--format=CSV"]).unwrap().command
+        else {
+            panic!("expected licenses");
+        };
+        assert_eq!((path, format), (PathBuf::from("report.txt"), Some(ReportFormat::Csv)));
+        assert!(parse(&["licenses", "--format", "xml"]).unwrap_err().starts_with("Unknown report format: xml"));
+    }
+
+    #[test]
+    fn rejects_flags_for_the_wrong_command() {
+        assert!(parse(&["--format", "json"]).unwrap_err().starts_with("--format is only valid with licenses"));
+        assert!(parse(&["export", "--format", "json"]).unwrap_err().starts_with("--format is only valid with licenses"));
+        assert!(parse(&["licenses", "--force"]).unwrap_err().starts_with("--force is only valid with export"));
+        assert!(parse(&["frobnicate"]).unwrap_err().starts_with("Unknown command: frobnicate"));
+        assert!(parse(&["--frobnicate"]).unwrap_err().starts_with("Unknown argument: --frobnicate"));
+    }
+}
@@ -0,0 +1,106 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::cli::Cli;
+use crate::policy::Policy;
+
+pub const DEFAULT_API_DOMAIN: &str = "https://formulae.brew.sh/api";
+
+/// The on-disk config file. Every key is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    api_domain: Option<String>,
+    /// Team manifest, relative to the config file's directory.
+    manifest: Option<PathBuf>,
+    policy: Policy,
+}
+
+/// Settings resolved from the command line, environment and config file.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub debug: bool,
+    /// Base URL of the Homebrew JSON API, without a trailing slash.
+    pub api_domain: String,
+    /// Whether Homebrew's local API cache describes the same catalog as
+    /// `api_domain`, i.e. taphouse and brew are talking to the same server.
+    pub use_homebrew_cache: bool,
+    /// A Brewfile whose plan is shown at startup.
+    pub brewfile: Option<PathBuf>,
+    /// Team manifest for the Compliance tab.
+    pub manifest: Option<PathBuf>,
+    pub policy: Policy,
+}
+
+/// `$XDG_CONFIG_HOME/taphouse/config.toml`, falling back to
+/// `~/.config/taphouse/config.toml`.
+pub fn default_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+    Some(base.join("taphouse").join("config.toml"))
+}
+
+impl Config {
+    /// Resolves settings. The API domain is taken from `--api-domain`, then
+    /// `HOMEBREW_API_DOMAIN`, then the config file, then the public API.
+    pub fn load(cli: &Cli) -> Result<Self, String> {
+        let (file, file_path) = match &cli.config_path {
+            // An explicitly requested config file must exist.
+            Some(path) => (
+                read_config_file(path)?.ok_or_else(|| format!("Config file {} not found", path.display()))?,
+                Some(path.clone()),
+            ),
+            None => match default_config_path() {
+                Some(path) => (read_config_file(&path)?.unwrap_or_default(), Some(path)),
+                None => (ConfigFile::default(), None),
+            },
+        };
+        let config_dir = file_path.as_deref().and_then(std::path::Path::parent);
+        let manifest = cli.manifest.clone().or_else(|| {
+            let path = file.manifest.clone()?;
+            Some(match config_dir {
+                Some(dir) if path.is_relative() => dir.join(path),
+                _ => path,
+            })
+        });
+
+        let env_domain = std::env::var("HOMEBREW_API_DOMAIN")
+            .ok()
+            .filter(|v| !v.is_empty());
+        let brew_domain = normalize_domain(env_domain.as_deref().unwrap_or(DEFAULT_API_DOMAIN));
+        let api_domain = normalize_domain(
+            cli.api_domain
+                .as_deref()
+                .or(env_domain.as_deref())
+                .or(file.api_domain.as_deref())
+                .unwrap_or(DEFAULT_API_DOMAIN),
+        );
+
+        Ok(Config {
+            debug: cli.debug,
+            use_homebrew_cache: api_domain == brew_domain,
+            brewfile: cli.brewfile.clone(),
+            manifest,
+            policy: file.policy,
+            api_domain,
+        })
+    }
+}
+
+fn read_config_file(path: &std::path::Path) -> Result<Option<ConfigFile>, String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    toml::from_str(&text)
+        .map(Some)
+        .map_err(|e| format!("Invalid config file {}: {e}", path.display()))
+}
+
+fn normalize_domain(domain: &str) -> String {
+    domain.trim().trim_end_matches('/').to_string()
+}
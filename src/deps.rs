@@ -0,0 +1,309 @@
+use std::collections::{HashMap, HashSet};
+
+use ratatui::widgets::ListState;
+
+use crate::brew::types::{CaskDependsOn, CaskInfo, FormulaInfo, InstalledCask, InstalledFormula, PackageKind};
+
+/// Third-party dependencies are listed as `user/tap/name`; the catalog and
+/// the installed list key formulae by their short name.
+pub fn short_name(dep: &str) -> &str {
+    dep.rsplit('/').next().unwrap_or(dep)
+}
+
+/// Formula dependencies from the loaded catalog, falling back to the
+/// installed formulae when the catalog hasn't been loaded (or doesn't know a
+/// formula, e.g. one from a private tap).
+pub struct DepGraph<'a> {
+    deps: HashMap<&'a str, &'a [String]>,
+    installed: HashSet<&'a str>,
+}
+
+impl<'a> DepGraph<'a> {
+    pub fn new(catalog: Option<&'a [FormulaInfo]>, installed: &'a [InstalledFormula]) -> Self {
+        let mut deps: HashMap<&str, &[String]> = installed
+            .iter()
+            .map(|f| (f.name.as_str(), f.dependencies.as_slice()))
+            .collect();
+        for f in catalog.unwrap_or_default() {
+            deps.insert(f.name.as_str(), f.dependencies.as_slice());
+        }
+        let installed = installed.iter().map(|f| f.name.as_str()).collect();
+        Self { deps, installed }
+    }
+
+    /// Direct dependencies of `name`, or `None` if the formula is unknown.
+    pub fn dependencies(&self, name: &str) -> Option<&'a [String]> {
+        self.deps.get(short_name(name)).copied()
+    }
+
+    pub fn is_installed(&self, name: &str) -> bool {
+        self.installed.contains(short_name(name))
+    }
+
+    /// Every formula `root` pulls in, directly or transitively, in the order
+    /// first reached.
+    pub fn closure(&self, root: &str) -> Vec<&'a str> {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut order = vec![];
+        let mut stack: Vec<&str> = self
+            .dependencies(root)
+            .unwrap_or_default()
+            .iter()
+            .rev()
+            .map(|d| short_name(d))
+            .collect();
+        while let Some(name) = stack.pop() {
+            if name == short_name(root) || !seen.insert(name) {
+                continue;
+            }
+            order.push(name);
+            let children = self.dependencies(name).unwrap_or_default();
+            stack.extend(children.iter().rev().map(|d| short_name(d)));
+        }
+        order
+    }
+}
+
+/// Dependents of each package, keyed by kind and short name.
+#[derive(Debug, Default)]
+struct Dependents {
+    formulae: HashMap<String, Vec<String>>,
+    casks: HashMap<String, Vec<String>>,
+}
+
+impl Dependents {
+    fn add(&mut self, kind: PackageKind, dep: &str, dependent: &str) {
+        let map = match kind {
+            PackageKind::Formula => &mut self.formulae,
+            PackageKind::Cask => &mut self.casks,
+        };
+        map.entry(short_name(dep).to_string()).or_default().push(dependent.to_string());
+    }
+
+    fn get(&self, kind: PackageKind, name: &str) -> &[String] {
+        let map = match kind {
+            PackageKind::Formula => &self.formulae,
+            PackageKind::Cask => &self.casks,
+        };
+        map.get(short_name(name)).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    fn finish(&mut self) {
+        for names in self.formulae.values_mut().chain(self.casks.values_mut()) {
+            names.sort_unstable();
+            names.dedup();
+        }
+    }
+}
+
+/// "Used by" lookups: which packages depend on a given one. Formulae depend
+/// only on formulae; casks can depend on both.
+#[derive(Debug, Default)]
+pub struct ReverseIndex {
+    /// Installed packages that need the key: formulae whose installed kegs
+    /// need it at runtime, directly or through other dependencies, and casks
+    /// that declare it.
+    installed: Dependents,
+    /// Catalog packages that declare the key as a direct dependency.
+    all: Dependents,
+}
+
+impl ReverseIndex {
+    pub fn new(
+        formulae: Option<&[FormulaInfo]>,
+        casks: Option<&[CaskInfo]>,
+        installed_formulae: &[InstalledFormula],
+        installed_casks: &[InstalledCask],
+    ) -> Self {
+        let mut index = ReverseIndex::default();
+        for f in installed_formulae {
+            // Receipts list the full runtime closure; declared dependencies
+            // cover formulae installed before receipts recorded it.
+            let runtime = f
+                .installed
+                .iter()
+                .flat_map(|k| &k.runtime_dependencies)
+                .map(|d| d.full_name.as_str());
+            let declared = f.dependencies.iter().map(String::as_str);
+            let deps: HashSet<&str> = runtime.chain(declared).map(short_name).collect();
+            for dep in deps {
+                index.installed.add(PackageKind::Formula, dep, &f.name);
+            }
+        }
+        for c in installed_casks {
+            add_cask(&mut index.installed, &c.token, &c.depends_on);
+        }
+        for f in formulae.unwrap_or_default() {
+            for dep in &f.dependencies {
+                index.all.add(PackageKind::Formula, dep, &f.name);
+            }
+        }
+        for c in casks.unwrap_or_default() {
+            add_cask(&mut index.all, &c.token, &c.depends_on);
+        }
+        index.installed.finish();
+        index.all.finish();
+        index
+    }
+
+    /// Installed packages that would break if `name` were removed.
+    pub fn used_by_installed(&self, kind: PackageKind, name: &str) -> &[String] {
+        self.installed.get(kind, name)
+    }
+
+    /// Every catalog package that depends on `name` directly.
+    pub fn used_by_all(&self, kind: PackageKind, name: &str) -> &[String] {
+        self.all.get(kind, name)
+    }
+}
+
+fn add_cask(dependents: &mut Dependents, token: &str, depends_on: &CaskDependsOn) {
+    for dep in &depends_on.formula {
+        dependents.add(PackageKind::Formula, dep, token);
+    }
+    for dep in &depends_on.cask {
+        dependents.add(PackageKind::Cask, dep, token);
+    }
+}
+
+/// Formulae `brew autoremove` would remove: installed as a dependency and no
+/// longer needed, directly or transitively, by another formula or by an
+/// installed cask's `depends_on`.
+pub fn autoremovable<'a>(installed: &'a [InstalledFormula], casks: &'a [InstalledCask]) -> Vec<&'a str> {
+    let by_name: HashMap<&str, &InstalledFormula> = installed.iter().map(|f| (f.name.as_str(), f)).collect();
+    let mut needed: HashSet<&str> = HashSet::new();
+    let mut stack: Vec<&str> = installed
+        .iter()
+        .filter(|f| !f.is_dependency_install())
+        .map(|f| f.name.as_str())
+        .chain(casks.iter().flat_map(|c| &c.depends_on.formula).map(|d| short_name(d)))
+        .collect();
+    while let Some(name) = stack.pop() {
+        if !needed.insert(name) {
+            continue;
+        }
+        let Some(f) = by_name.get(name) else {
+            continue;
+        };
+        let runtime = f.installed.iter().flat_map(|k| &k.runtime_dependencies).map(|d| d.full_name.as_str());
+        let declared = f.dependencies.iter().map(String::as_str);
+        stack.extend(runtime.chain(declared).map(short_name));
+    }
+    installed
+        .iter()
+        .map(|f| f.name.as_str())
+        .filter(|name| !needed.contains(name))
+        .collect()
+}
+
+/// A visible line of the dependency tree.
+#[derive(Debug, Clone)]
+pub struct TreeRow {
+    pub name: String,
+    /// Names from the root down to and including this node.
+    pub path: Vec<String>,
+    pub has_children: bool,
+    pub expanded: bool,
+    pub installed: bool,
+    /// The node already appears above itself on its path.
+    pub cycle: bool,
+    /// Neither the catalog nor the installed list knows this formula.
+    pub unknown: bool,
+}
+
+impl TreeRow {
+    pub fn depth(&self) -> usize {
+        self.path.len() - 1
+    }
+}
+
+/// The collapsible dependency tree for one formula. Nodes are identified by
+/// their path from the root, so a formula reached along two routes can be
+/// expanded in one place and collapsed in the other.
+#[derive(Debug, Default)]
+pub struct DepTree {
+    pub root: String,
+    expanded: HashSet<Vec<String>>,
+    pub state: ListState,
+}
+
+impl DepTree {
+    pub fn new(root: String) -> Self {
+        let mut expanded = HashSet::new();
+        expanded.insert(vec![root.clone()]);
+        let mut state = ListState::default();
+        state.select(Some(0));
+        Self { root, expanded, state }
+    }
+
+    pub fn rows(&self, graph: &DepGraph) -> Vec<TreeRow> {
+        let mut rows = vec![];
+        self.walk(graph, vec![self.root.clone()], &mut rows);
+        rows
+    }
+
+    fn walk(&self, graph: &DepGraph, path: Vec<String>, rows: &mut Vec<TreeRow>) {
+        let name = path.last().cloned().unwrap_or_default();
+        let cycle = path[..path.len() - 1].contains(&name);
+        let deps = graph.dependencies(&name);
+        let has_children = !cycle && deps.is_some_and(|d| !d.is_empty());
+        let expanded = has_children && self.expanded.contains(&path);
+        rows.push(TreeRow {
+            name: name.clone(),
+            path: path.clone(),
+            has_children,
+            expanded,
+            installed: graph.is_installed(&name),
+            cycle,
+            unknown: deps.is_none(),
+        });
+        if expanded {
+            for dep in deps.unwrap_or_default() {
+                let mut child = path.clone();
+                child.push(short_name(dep).to_string());
+                self.walk(graph, child, rows);
+            }
+        }
+    }
+
+    pub fn set_expanded(&mut self, path: &[String], expanded: bool) {
+        if expanded {
+            self.expanded.insert(path.to_vec());
+        } else {
+            self.expanded.remove(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formula(name: &str, deps: &[&str], on_request: bool) -> InstalledFormula {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "full_name": name,
+            "dependencies": deps,
+            "installed": [{ "version": "1.0", "installed_on_request": on_request, "installed_as_dependency": !on_request }],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn autoremove_keeps_cask_dependencies_and_their_closure() {
+        let formulae = [
+            formula("git", &["pcre2"], true),
+            formula("pcre2", &[], false),
+            formula("ffmpeg", &["x264"], false),
+            formula("x264", &[], false),
+            formula("orphan", &[], false),
+        ];
+        let cask: InstalledCask = serde_json::from_value(serde_json::json!({
+            "token": "obs",
+            "depends_on": { "formula": ["homebrew/core/ffmpeg"], "macos": { ">=": ["12"] } },
+        }))
+        .unwrap();
+        assert_eq!(autoremovable(&formulae, &[]), ["ffmpeg", "x264", "orphan"]);
+        assert_eq!(autoremovable(&formulae, &[cask]), ["orphan"]);
+    }
+}
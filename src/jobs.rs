@@ -0,0 +1,116 @@
+use tokio::sync::oneshot;
+
+use crate::app::BrewAction;
+
+pub type JobId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Succeeded | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+#[derive(Debug)]
+pub struct Job {
+    pub id: JobId,
+    pub action: BrewAction,
+    pub status: JobStatus,
+    pub output: Vec<String>,
+    pub exit_code: Option<i32>,
+    /// Signals the running brew process to stop; set while the job runs.
+    pub cancel: Option<oneshot::Sender<()>>,
+}
+
+impl Job {
+    /// The command line this job runs, for display.
+    pub fn command_line(&self) -> String {
+        format!("brew {}", self.action.args().join(" "))
+    }
+}
+
+/// Confirmed brew actions, executed one at a time in submission order.
+#[derive(Debug, Default)]
+pub struct JobQueue {
+    jobs: Vec<Job>,
+    next_id: JobId,
+}
+
+impl JobQueue {
+    pub fn enqueue(&mut self, action: BrewAction) -> JobId {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.jobs.push(Job {
+            id,
+            action,
+            status: JobStatus::Queued,
+            output: vec![],
+            exit_code: None,
+            cancel: None,
+        });
+        id
+    }
+
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    pub fn get_mut(&mut self, id: JobId) -> Option<&mut Job> {
+        self.jobs.iter_mut().find(|j| j.id == id)
+    }
+
+    pub fn running(&self) -> Option<&Job> {
+        self.jobs.iter().find(|j| j.status == JobStatus::Running)
+    }
+
+    pub fn queued_count(&self) -> usize {
+        self.jobs.iter().filter(|j| j.status == JobStatus::Queued).count()
+    }
+
+    /// Marks the oldest queued job as running and returns it, unless a job
+    /// is already running.
+    pub fn start_next(&mut self) -> Option<&mut Job> {
+        if self.running().is_some() {
+            return None;
+        }
+        let job = self.jobs.iter_mut().find(|j| j.status == JobStatus::Queued)?;
+        job.status = JobStatus::Running;
+        Some(job)
+    }
+
+    /// Cancels a job. Queued jobs are cancelled immediately; running jobs
+    /// are asked to stop and report back once the process has exited.
+    /// Returns false if the job had already finished.
+    pub fn cancel(&mut self, id: JobId) -> bool {
+        let Some(job) = self.get_mut(id) else {
+            return false;
+        };
+        match job.status {
+            JobStatus::Queued => {
+                job.status = JobStatus::Cancelled;
+                job.output.push("Cancelled before it started.".to_string());
+                true
+            }
+            JobStatus::Running => {
+                if let Some(cancel) = job.cancel.take() {
+                    let _ = cancel.send(());
+                    job.output.push("Cancelling…".to_string());
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn clear_finished(&mut self) {
+        self.jobs.retain(|j| !j.status.is_finished());
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::brew::types::{FormulaInfo, InstalledFormula};
+
+/// SPDX identifier prefixes of copyleft licenses, weak and strong.
+const COPYLEFT_PREFIXES: [&str; 10] = [
+    "GPL-", "LGPL-", "AGPL-", "MPL-", "EPL-", "EUPL-", "CDDL-", "OSL-", "CC-BY-SA-", "SSPL-",
+];
+
+/// The license identifiers an SPDX expression mentions, e.g. `MIT` and
+/// `Apache-2.0` for `Apache-2.0 OR MIT`.
+pub fn license_ids(expression: &str) -> impl Iterator<Item = &str> {
+    expression
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .filter(|t| !t.is_empty() && !matches!(t.to_ascii_uppercase().as_str(), "AND" | "OR" | "WITH"))
+}
+
+/// Whether an expression mentions a copyleft license. Alternatives count
+/// too, since legal review wants to see `MIT OR GPL-2.0-only` as well.
+pub fn is_copyleft(expression: &str) -> bool {
+    license_ids(expression).any(|id| {
+        let id = id.to_ascii_uppercase();
+        COPYLEFT_PREFIXES.iter().any(|p| id.starts_with(p))
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LicensedPackage {
+    pub name: String,
+    pub version: String,
+}
+
+/// Installed formulae sharing one license expression.
+#[derive(Debug, Clone, Serialize)]
+pub struct LicenseGroup {
+    /// `None` when neither the catalog nor the receipt names a license.
+    pub license: Option<String>,
+    pub unknown: bool,
+    pub copyleft: bool,
+    pub packages: Vec<LicensedPackage>,
+}
+
+impl LicenseGroup {
+    pub fn label(&self) -> &str {
+        self.license.as_deref().unwrap_or("Unknown")
+    }
+}
+
+/// Groups installed formulae by license, read from the catalog and falling
+/// back to the installed formula's own metadata. Groups are sorted by
+/// license, with unknown licenses last.
+pub fn audit(catalog: Option<&[FormulaInfo]>, installed: &[InstalledFormula]) -> Vec<LicenseGroup> {
+    let catalog: BTreeMap<&str, &FormulaInfo> = catalog
+        .unwrap_or_default()
+        .iter()
+        .map(|f| (f.full_name.as_str(), f))
+        .collect();
+    let mut groups: BTreeMap<Option<String>, Vec<LicensedPackage>> = BTreeMap::new();
+    for f in installed {
+        let license = catalog
+            .get(f.full_name.as_str())
+            .and_then(|c| c.license.clone())
+            .or_else(|| f.license.clone())
+            .filter(|l| !l.is_empty() && l != "NOASSERTION");
+        let version = f.latest_keg().map(|k| k.version.clone()).unwrap_or_default();
+        groups.entry(license).or_default().push(LicensedPackage { name: f.name.clone(), version });
+    }
+    let mut groups: Vec<LicenseGroup> = groups
+        .into_iter()
+        .map(|(license, mut packages)| {
+            packages.sort_by(|a, b| a.name.cmp(&b.name));
+            let copyleft = license.as_deref().is_some_and(is_copyleft);
+            LicenseGroup { unknown: license.is_none(), license, copyleft, packages }
+        })
+        .collect();
+    // `None` sorts first in a BTreeMap.
+    let unknown = groups.iter().take_while(|g| g.unknown).count();
+    groups.rotate_left(unknown);
+    groups
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl ReportFormat {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            "md" | "markdown" => Ok(ReportFormat::Markdown),
+            other => Err(format!("Unknown report format: {other} (expected json, csv or markdown)")),
+        }
+    }
+
+    /// Picks the format from a file extension, defaulting to Markdown.
+    pub fn for_path(path: &Path) -> Self {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .and_then(|e| Self::parse(e).ok())
+            .unwrap_or(ReportFormat::Markdown)
+    }
+}
+
+pub fn render_report(groups: &[LicenseGroup], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Json => {
+            #[derive(Serialize)]
+            struct Report<'a> {
+                formulae: usize,
+                licenses: &'a [LicenseGroup],
+            }
+            let formulae = groups.iter().map(|g| g.packages.len()).sum();
+            let mut out = serde_json::to_string_pretty(&Report { formulae, licenses: groups }).unwrap_or_default();
+            out.push('\n');
+            out
+        }
+        ReportFormat::Csv => {
+            let mut out = "license,copyleft,unknown,formula,version\n".to_string();
+            for g in groups {
+                for p in &g.packages {
+                    let _ = writeln!(
+                        out,
+                        "{},{},{},{},{}",
+                        csv_field(g.label()),
+                        g.copyleft,
+                        g.unknown,
+                        csv_field(&p.name),
+                        csv_field(&p.version)
+                    );
+                }
+            }
+            out
+        }
+        ReportFormat::Markdown => {
+            let formulae: usize = groups.iter().map(|g| g.packages.len()).sum();
+            let copyleft: usize = groups.iter().filter(|g| g.copyleft).map(|g| g.packages.len()).sum();
+            let unknown: usize = groups.iter().filter(|g| g.unknown).map(|g| g.packages.len()).sum();
+            let mut out = "# License audit\n\n".to_string();
+            let _ = writeln!(
+                out,
+                "{formulae} installed formulae under {} licenses: {copyleft} copyleft, {unknown} unknown.\n",
+                groups.iter().filter(|g| g.license.is_some()).count()
+            );
+            out.push_str("| License | Flags | Count | Formulae |\n|---|---|---|---|\n");
+            for g in groups {
+                let flags = match (g.unknown, g.copyleft) {
+                    (true, _) => "unknown",
+                    (_, true) => "copyleft",
+                    _ => "",
+                };
+                let names: Vec<String> = g.packages.iter().map(|p| format!("{} {}", p.name, p.version)).collect();
+                let _ = writeln!(
+                    out,
+                    "| {} | {flags} | {} | {} |",
+                    g.label().replace('|', "\\|"),
+                    g.packages.len(),
+                    names.join(", ")
+                );
+            }
+            out
+        }
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Writes a report to `path`, or to stdout for `-`. Reports are derived
+/// data, so an existing file is replaced.
+pub fn write_report(path: &Path, groups: &[LicenseGroup], format: ReportFormat) -> Result<(), String> {
+    let report = render_report(groups, format);
+    if path == Path::new("-") {
+        print!("{report}");
+        return Ok(());
+    }
+    std::fs::write(path, report).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
@@ -1,10 +1,21 @@
 mod app;
 mod brew;
+mod brewfile;
+mod cli;
+mod config;
+mod deps;
+mod jobs;
+mod license;
+mod manifest;
+mod policy;
+mod query;
+mod search;
+mod sort;
 mod ui;
 
 use std::io;
 
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyEventKind},
     execute,
@@ -14,11 +25,24 @@ use ratatui::{backend::CrosstermBackend, Terminal};
 use tokio::sync::mpsc;
 
 use app::{App, AppEvent};
+use config::Config;
+use manifest::Manifest;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
 
+    let cli = cli::Cli::parse().map_err(|e| eyre!(e))?;
+    if cli.help {
+        print!("{}", cli::USAGE);
+        return Ok(());
+    }
+    if let Some(command) = &cli.command {
+        return run_command(command, &cli).await;
+    }
+    let config = Config::load(&cli).map_err(|e| eyre!(e))?;
+    let manifest = config.manifest.as_deref().map(Manifest::load).transpose().map_err(|e| eyre!(e))?;
+
     // Terminal setup
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -26,7 +50,7 @@ async fn main() -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let result = run(&mut terminal).await;
+    let result = run(&mut terminal, config, manifest).await;
 
     // Terminal teardown (always restore, even on error)
     disable_raw_mode()?;
@@ -40,30 +64,52 @@ async fn main() -> Result<()> {
     result
 }
 
-async fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
-    let debug_mode = std::env::args().any(|a| a == "--debug");
+async fn run_command(command: &cli::Command, cli: &cli::Cli) -> Result<()> {
+    match command {
+        cli::Command::Export { path, options, force } => {
+            let entries = brewfile::export(path, *options, *force).map_err(|e| eyre!(e))?;
+            if path.as_os_str() != "-" {
+                eprintln!("Wrote {entries} entries to {}", path.display());
+            }
+        }
+        cli::Command::Licenses { path, format } => {
+            // Only the catalog lookup needs settings.
+            let config = Config::load(cli).map_err(|e| eyre!(e))?;
+            let installed = brew::installed::list_installed().map_err(|e| eyre!(e))?;
+            let client = brew::api::client();
+            let catalog = match brew::api::fetch_formulae(&client, &config.api_domain, config.use_homebrew_cache).await {
+                Ok((data, _)) => Some(data),
+                Err(e) => {
+                    eprintln!("Warning: {e}; using the licenses recorded for installed formulae");
+                    None
+                }
+            };
+            let groups = license::audit(catalog.as_deref(), &installed.formulae);
+            let format = format.unwrap_or_else(|| license::ReportFormat::for_path(path));
+            license::write_report(path, &groups, format).map_err(|e| eyre!(e))?;
+            if path.as_os_str() != "-" {
+                eprintln!("Wrote a license report for {} formulae to {}", installed.formulae.len(), path.display());
+            }
+        }
+    }
+    Ok(())
+}
 
+async fn run(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    config: Config,
+    manifest: Option<Manifest>,
+) -> Result<()> {
     let (event_tx, mut event_rx) = mpsc::channel::<AppEvent>(256);
 
-    let mut app = App::new(event_tx.clone(), debug_mode);
+    let mut app = App::new(event_tx.clone(), config);
+    app.set_manifest(manifest);
+    if let Some(path) = app.config.brewfile.clone() {
+        app.open_brewfile(&path);
+    }
 
     // Load installed packages at startup
-    {
-        let tx = event_tx.clone();
-        tokio::spawn(async move {
-            let formulae = match tokio::task::spawn_blocking(brew::installed::list_formulae).await {
-                Ok(Ok(v)) => v,
-                Ok(Err(e)) => { let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] {e}"))).await; vec![] }
-                Err(e) => { let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] spawn failed: {e}"))).await; vec![] }
-            };
-            let casks = match tokio::task::spawn_blocking(brew::installed::list_casks).await {
-                Ok(Ok(v)) => v,
-                Ok(Err(e)) => { let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] {e}"))).await; vec![] }
-                Err(e) => { let _ = tx.send(AppEvent::DebugLog(format!("[ERROR] spawn failed: {e}"))).await; vec![] }
-            };
-            let _ = tx.send(AppEvent::InstalledLoaded { formulae, casks }).await;
-        });
-    }
+    app::spawn_installed_load(event_tx.clone());
 
     loop {
         terminal.draw(|f| ui::render(f, &app))?;
@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::brew::types::{InstalledCask, InstalledFormula, TapInfo};
+use crate::brewfile::{cask_matches, formula_matches, EntryKind};
+use crate::search::Searchable;
+use crate::sort::compare_versions;
+
+/// Formulae, casks and taps named by one section of a manifest.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PackageSet {
+    pub formulae: Vec<String>,
+    pub casks: Vec<String>,
+    pub taps: Vec<String>,
+}
+
+impl PackageSet {
+    fn entries(&self) -> impl Iterator<Item = (EntryKind, &str)> {
+        let taps = self.taps.iter().map(|n| (EntryKind::Tap, n.as_str()));
+        let formulae = self.formulae.iter().map(|n| (EntryKind::Brew, n.as_str()));
+        let casks = self.casks.iter().map(|n| (EntryKind::Cask, n.as_str()));
+        taps.chain(formulae).chain(casks)
+    }
+}
+
+/// A team manifest: what every laptop must have, what it may have, and what
+/// it must not.
+///
+/// ```toml
+/// [required]
+/// formulae = ["git", "jq"]
+/// casks = ["docker"]
+///
+/// [groups.frontend]
+/// formulae = ["node"]
+///
+/// [forbidden]
+/// formulae = ["telnet"]
+///
+/// [minimum_versions]
+/// git = "2.40"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Manifest {
+    pub required: PackageSet,
+    /// Optional groups by name. Missing packages are reported but don't
+    /// fail the check.
+    pub groups: BTreeMap<String, PackageSet>,
+    pub forbidden: PackageSet,
+    /// Minimum installed version per formula or cask name.
+    pub minimum_versions: BTreeMap<String, String>,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        let mut manifest: Manifest =
+            toml::from_str(&text).map_err(|e| format!("Invalid manifest {}: {e}", path.display()))?;
+        manifest.path = path.to_path_buf();
+        Ok(manifest)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    Required,
+    Optional { group: String },
+    Forbidden,
+    MinimumVersion { minimum: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Fail,
+    /// Nothing to check: an optional package that isn't installed, a
+    /// minimum version for something not installed, or a rule whose packages
+    /// or taps are still loading.
+    Skipped,
+}
+
+/// The result of checking one rule against the installed state.
+#[derive(Debug, Clone)]
+pub struct Check {
+    /// Unique per manifest, e.g. `required brew git`.
+    pub label: String,
+    pub rule: Rule,
+    pub kind: EntryKind,
+    pub name: String,
+    pub outcome: Outcome,
+    pub detail: String,
+}
+
+impl Searchable for Check {
+    fn search_name(&self) -> &str {
+        &self.label
+    }
+
+    fn search_fields(&self) -> Vec<&str> {
+        vec![self.detail.as_str()]
+    }
+}
+
+/// Checks every rule in `manifest`. Tap rules are skipped while `taps` is
+/// unknown.
+pub fn check(
+    manifest: &Manifest,
+    installed: Option<(&[InstalledFormula], &[InstalledCask])>,
+    taps: Option<&[TapInfo]>,
+) -> Vec<Check> {
+    let installed_version = |kind: EntryKind, name: &str| -> Option<Option<String>> {
+        match (kind, installed) {
+            (EntryKind::Tap, _) => {
+                taps.map(|taps| taps.iter().any(|t| t.name.eq_ignore_ascii_case(name)).then(String::new))
+            }
+            (EntryKind::Brew, Some((formulae, _))) => Some(
+                formulae
+                    .iter()
+                    .find(|f| formula_matches(f, name))
+                    .map(|f| f.latest_keg().map(|k| k.version.clone()).unwrap_or_default()),
+            ),
+            (EntryKind::Cask, Some((_, casks))) => Some(
+                casks
+                    .iter()
+                    .find(|c| cask_matches(c, name))
+                    .map(|c| c.installed.clone().unwrap_or_else(|| c.version.clone())),
+            ),
+            (_, None) => None,
+        }
+    };
+    let loading = |kind: EntryKind| match kind {
+        EntryKind::Tap => "Taps are still loading".to_string(),
+        _ => "Installed packages are still loading".to_string(),
+    };
+    let mut checks = vec![];
+    let mut push = |rule: Rule, label: String, kind: EntryKind, name: &str, outcome: Outcome, detail: String| {
+        checks.push(Check { label, rule, kind, name: name.to_string(), outcome, detail });
+    };
+
+    for (kind, name) in manifest.required.entries() {
+        let label = format!("required {} {name}", kind.as_str());
+        match installed_version(kind, name) {
+            None => push(Rule::Required, label, kind, name, Outcome::Skipped, loading(kind)),
+            Some(Some(_)) => push(Rule::Required, label, kind, name, Outcome::Pass, "Installed".to_string()),
+            Some(None) => push(Rule::Required, label, kind, name, Outcome::Fail, "Not installed".to_string()),
+        }
+    }
+    for (group, set) in &manifest.groups {
+        for (kind, name) in set.entries() {
+            let label = format!("optional ({group}) {} {name}", kind.as_str());
+            let rule = Rule::Optional { group: group.clone() };
+            match installed_version(kind, name) {
+                None => push(rule, label, kind, name, Outcome::Skipped, loading(kind)),
+                Some(Some(_)) => push(rule, label, kind, name, Outcome::Pass, "Installed".to_string()),
+                Some(None) => push(rule, label, kind, name, Outcome::Skipped, "Not installed (optional)".to_string()),
+            }
+        }
+    }
+    for (kind, name) in manifest.forbidden.entries() {
+        let label = format!("forbidden {} {name}", kind.as_str());
+        match installed_version(kind, name) {
+            None => push(Rule::Forbidden, label, kind, name, Outcome::Skipped, loading(kind)),
+            Some(Some(_)) => push(Rule::Forbidden, label, kind, name, Outcome::Fail, "Installed".to_string()),
+            Some(None) => push(Rule::Forbidden, label, kind, name, Outcome::Pass, "Not installed".to_string()),
+        }
+    }
+    for (name, minimum) in &manifest.minimum_versions {
+        let label = format!("minimum {name} {minimum}");
+        let rule = Rule::MinimumVersion { minimum: minimum.clone() };
+        // The name may be a formula or a cask.
+        let found = [EntryKind::Brew, EntryKind::Cask]
+            .into_iter()
+            .find_map(|kind| Some((kind, installed_version(kind, name)??)));
+        match found {
+            None if installed.is_none() => push(rule, label, EntryKind::Brew, name, Outcome::Skipped, loading(EntryKind::Brew)),
+            None => push(rule, label, EntryKind::Brew, name, Outcome::Skipped, "Not installed".to_string()),
+            Some((kind, version)) if compare_versions(&version, minimum).is_lt() => {
+                let detail = format!("{version} installed, {minimum} or newer required");
+                push(rule, label, kind, name, Outcome::Fail, detail);
+            }
+            Some((kind, version)) => push(rule, label, kind, name, Outcome::Pass, format!("{version} installed")),
+        }
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(checks: &[Check]) -> Vec<(&str, Outcome, &str)> {
+        checks.iter().map(|c| (c.label.as_str(), c.outcome, c.detail.as_str())).collect()
+    }
+
+    #[test]
+    fn optional_rules_wait_for_installed_packages() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            [groups.frontend]
+            formulae = ["node", "yarn"]
+            taps = ["acme/tools"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            details(&check(&manifest, None, None)),
+            [
+                ("optional (frontend) tap acme/tools", Outcome::Skipped, "Taps are still loading"),
+                ("optional (frontend) brew node", Outcome::Skipped, "Installed packages are still loading"),
+                ("optional (frontend) brew yarn", Outcome::Skipped, "Installed packages are still loading"),
+            ]
+        );
+
+        let node: InstalledFormula = serde_json::from_value(serde_json::json!({
+            "name": "node",
+            "full_name": "node",
+            "installed": [{ "version": "22.1.0" }],
+        }))
+        .unwrap();
+        let taps: Vec<TapInfo> = serde_json::from_value(serde_json::json!([{ "name": "acme/tools" }])).unwrap();
+        assert_eq!(
+            details(&check(&manifest, Some((&[node], &[])), Some(&taps))),
+            [
+                ("optional (frontend) tap acme/tools", Outcome::Pass, "Installed"),
+                ("optional (frontend) brew node", Outcome::Pass, "Installed"),
+                ("optional (frontend) brew yarn", Outcome::Skipped, "Not installed (optional)"),
+            ]
+        );
+    }
+}
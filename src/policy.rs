@@ -0,0 +1,198 @@
+use serde::Deserialize;
+
+use crate::brew::types::PackageKind;
+use crate::license::license_ids;
+
+/// Which packages, taps and licenses may be installed, from the `[policy]`
+/// section of the config file. Deny lists always win; an allow list, when
+/// set, admits only what it names.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    pub deny_formulae: Vec<String>,
+    pub deny_casks: Vec<String>,
+    pub deny_taps: Vec<String>,
+    /// SPDX identifiers, e.g. `AGPL-3.0-only`.
+    pub deny_licenses: Vec<String>,
+    pub allow_formulae: Option<Vec<String>>,
+    pub allow_casks: Option<Vec<String>>,
+    pub allow_taps: Option<Vec<String>>,
+    pub allow_licenses: Option<Vec<String>>,
+}
+
+/// A package or tap the policy refused, and why.
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub name: String,
+    pub reason: String,
+}
+
+fn contains(list: &[String], name: &str) -> bool {
+    list.iter().any(|n| n.eq_ignore_ascii_case(name))
+}
+
+impl Policy {
+    /// Checks installing `name`. `tap` and `license` come from the catalog
+    /// when it knows the package; casks carry no license.
+    pub fn check_package(&self, kind: PackageKind, name: &str, tap: Option<&str>, license: Option<&str>) -> Option<String> {
+        if let Some(reason) = self.check_name(kind, name) {
+            return Some(reason);
+        }
+        // `user/repo/name` names its tap.
+        let tap = tap.or_else(|| name.rsplit_once('/').map(|(tap, _)| tap));
+        if let Some(reason) = tap.and_then(|tap| self.check_tap(tap)) {
+            return Some(reason);
+        }
+        if kind == PackageKind::Formula {
+            return self.check_license(license);
+        }
+        None
+    }
+
+    /// Checks `name` against the formula or cask lists alone.
+    pub fn check_name(&self, kind: PackageKind, name: &str) -> Option<String> {
+        let short = name.rsplit('/').next().unwrap_or(name);
+        let (label, deny, allow) = match kind {
+            PackageKind::Formula => ("Formula", &self.deny_formulae, &self.allow_formulae),
+            PackageKind::Cask => ("Cask", &self.deny_casks, &self.allow_casks),
+        };
+        if contains(deny, short) || contains(deny, name) {
+            return Some(format!("{label} {short} is denied"));
+        }
+        match allow {
+            Some(allow) if !contains(allow, short) && !contains(allow, name) => {
+                Some(format!("{label} {short} is not on the allow list"))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether checking `name` needs its catalog entry: for the tap of a
+    /// bare name when taps are restricted, or for a formula's license when
+    /// licenses are.
+    pub fn needs_catalog(&self, kind: PackageKind, name: &str) -> bool {
+        let taps = !self.deny_taps.is_empty() || self.allow_taps.is_some();
+        (taps && !name.contains('/')) || (kind == PackageKind::Formula && self.restricts_licenses())
+    }
+
+    fn restricts_licenses(&self) -> bool {
+        !self.deny_licenses.is_empty() || self.allow_licenses.is_some()
+    }
+
+    pub fn check_tap(&self, tap: &str) -> Option<String> {
+        if contains(&self.deny_taps, tap) {
+            return Some(format!("Tap {tap} is denied"));
+        }
+        match &self.allow_taps {
+            // The built-in taps are always allowed.
+            Some(allow) if !contains(allow, tap) && !matches!(tap, "homebrew/core" | "homebrew/cask") => {
+                Some(format!("Tap {tap} is not on the allow list"))
+            }
+            _ => None,
+        }
+    }
+
+    /// An expression is denied if it mentions a denied license, and allowed
+    /// only if every license it mentions is allowed. An unknown license
+    /// can't be shown to pass either rule, so it's denied by both.
+    fn check_license(&self, license: Option<&str>) -> Option<String> {
+        let Some(license) = license else {
+            return self
+                .restricts_licenses()
+                .then(|| "License is unknown and licenses are restricted".to_string());
+        };
+        if let Some(denied) = license_ids(license).find(|id| contains(&self.deny_licenses, id)) {
+            return Some(format!("License {denied} is denied"));
+        }
+        let allow = self.allow_licenses.as_ref()?;
+        license_ids(license)
+            .find(|id| !contains(allow, id))
+            .map(|id| format!("License {id} is not on the allow list"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn deny_list_wins_over_allow_list() {
+        let policy = Policy {
+            deny_formulae: strings(&["wget"]),
+            allow_formulae: Some(strings(&["wget", "jq"])),
+            ..Policy::default()
+        };
+        assert_eq!(policy.check_name(PackageKind::Formula, "WGET").unwrap(), "Formula WGET is denied");
+        assert_eq!(policy.check_name(PackageKind::Formula, "homebrew/core/wget").unwrap(), "Formula wget is denied");
+        assert_eq!(policy.check_name(PackageKind::Formula, "jq"), None);
+        assert_eq!(policy.check_name(PackageKind::Formula, "curl").unwrap(), "Formula curl is not on the allow list");
+        // Cask lists are separate.
+        assert_eq!(policy.check_name(PackageKind::Cask, "wget"), None);
+    }
+
+    #[test]
+    fn built_in_taps_are_always_allowed() {
+        let policy = Policy { allow_taps: Some(strings(&["acme/tools"])), ..Policy::default() };
+        assert_eq!(policy.check_tap("homebrew/core"), None);
+        assert_eq!(policy.check_tap("homebrew/cask"), None);
+        assert_eq!(policy.check_tap("acme/tools"), None);
+        assert_eq!(policy.check_tap("other/tap").unwrap(), "Tap other/tap is not on the allow list");
+
+        let policy = Policy { deny_taps: strings(&["homebrew/cask"]), ..Policy::default() };
+        assert_eq!(policy.check_tap("homebrew/cask").unwrap(), "Tap homebrew/cask is denied");
+    }
+
+    #[test]
+    fn licenses_check_every_identifier() {
+        let policy = Policy {
+            deny_licenses: strings(&["AGPL-3.0-only"]),
+            allow_licenses: Some(strings(&["MIT", "Apache-2.0"])),
+            ..Policy::default()
+        };
+        assert_eq!(policy.check_license(Some("MIT")), None);
+        assert_eq!(policy.check_license(Some("MIT AND Apache-2.0")), None);
+        assert_eq!(policy.check_license(Some("MIT OR AGPL-3.0-only")).unwrap(), "License AGPL-3.0-only is denied");
+        assert_eq!(policy.check_license(Some("(MIT OR BSD-3-Clause)")).unwrap(), "License BSD-3-Clause is not on the allow list");
+        assert_eq!(policy.check_license(None).unwrap(), "License is unknown and licenses are restricted");
+        assert_eq!(Policy::default().check_license(None), None);
+    }
+
+    #[test]
+    fn package_checks_name_then_tap_then_license() {
+        let policy = Policy {
+            deny_taps: strings(&["shady/tap"]),
+            deny_licenses: strings(&["GPL-3.0-only"]),
+            ..Policy::default()
+        };
+        assert_eq!(
+            policy.check_package(PackageKind::Formula, "shady/tap/tool", None, Some("MIT")).unwrap(),
+            "Tap shady/tap is denied"
+        );
+        assert_eq!(
+            policy.check_package(PackageKind::Formula, "tool", Some("shady/tap"), None).unwrap(),
+            "Tap shady/tap is denied"
+        );
+        assert_eq!(
+            policy.check_package(PackageKind::Formula, "tool", Some("homebrew/core"), Some("GPL-3.0-only")).unwrap(),
+            "License GPL-3.0-only is denied"
+        );
+        // Casks carry no license.
+        assert_eq!(policy.check_package(PackageKind::Cask, "tool", Some("homebrew/cask"), None), None);
+    }
+
+    #[test]
+    fn needs_catalog_only_for_what_the_name_lacks() {
+        let taps = Policy { deny_taps: strings(&["shady/tap"]), ..Policy::default() };
+        assert!(taps.needs_catalog(PackageKind::Cask, "tool"));
+        assert!(!taps.needs_catalog(PackageKind::Cask, "shady/tap/tool"));
+
+        let licenses = Policy { allow_licenses: Some(strings(&["MIT"])), ..Policy::default() };
+        assert!(licenses.needs_catalog(PackageKind::Formula, "user/tap/tool"));
+        assert!(!licenses.needs_catalog(PackageKind::Cask, "tool"));
+        assert!(!Policy::default().needs_catalog(PackageKind::Formula, "tool"));
+    }
+}
@@ -0,0 +1,288 @@
+use crate::brew::types::{CaskInfo, FormulaInfo, InstalledCask, InstalledFormula};
+
+/// A single `field:value` condition. Conditions in a query are AND-ed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// `tap:homebrew/core`, or `tap:homebrew` for every tap of that user.
+    Tap(String),
+    /// `license:MIT`, matched against each SPDX identifier by prefix, so
+    /// `license:GPL` covers `GPL-2.0-only` and `GPL-3.0-or-later`.
+    License(String),
+    /// `installed:yes|no`
+    Installed(bool),
+    /// `deprecated:true|false`
+    Deprecated(bool),
+    /// `dep:openssl@3`, a direct dependency.
+    Dep(String),
+    /// A filter prefixed with `-`, e.g. `-tap:homebrew/core`.
+    Not(Box<Filter>),
+}
+
+/// A parsed search query: free text for the fuzzy matcher plus filters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    pub text: String,
+    pub filters: Vec<Filter>,
+}
+
+/// Package attributes that filters can test.
+pub trait Filterable {
+    fn tap(&self) -> &str;
+    fn license(&self) -> Option<&str>;
+    fn deprecated(&self) -> bool;
+    fn dependencies(&self) -> &[String];
+}
+
+impl Filterable for FormulaInfo {
+    fn tap(&self) -> &str {
+        &self.tap
+    }
+
+    fn license(&self) -> Option<&str> {
+        self.license.as_deref()
+    }
+
+    fn deprecated(&self) -> bool {
+        self.deprecated
+    }
+
+    fn dependencies(&self) -> &[String] {
+        &self.dependencies
+    }
+}
+
+impl Filterable for CaskInfo {
+    fn tap(&self) -> &str {
+        &self.tap
+    }
+
+    fn license(&self) -> Option<&str> {
+        None
+    }
+
+    fn deprecated(&self) -> bool {
+        self.deprecated
+    }
+
+    fn dependencies(&self) -> &[String] {
+        &[]
+    }
+}
+
+impl Filterable for InstalledFormula {
+    fn tap(&self) -> &str {
+        &self.tap
+    }
+
+    fn license(&self) -> Option<&str> {
+        self.license.as_deref()
+    }
+
+    fn deprecated(&self) -> bool {
+        self.deprecated
+    }
+
+    fn dependencies(&self) -> &[String] {
+        &self.dependencies
+    }
+}
+
+impl Filterable for InstalledCask {
+    fn tap(&self) -> &str {
+        &self.tap
+    }
+
+    fn license(&self) -> Option<&str> {
+        None
+    }
+
+    fn deprecated(&self) -> bool {
+        self.deprecated
+    }
+
+    fn dependencies(&self) -> &[String] {
+        &[]
+    }
+}
+
+/// Parses a query such as `json tap:homebrew/core -license:GPL installed:no`.
+/// Words with an unknown `field:` prefix are kept as search text.
+pub fn parse(input: &str) -> Result<Query, String> {
+    let mut query = Query::default();
+    let mut text = vec![];
+
+    for token in tokenize(input) {
+        let (negated, body) = match token.strip_prefix('-') {
+            Some(rest) if rest.contains(':') => (true, rest),
+            _ => (false, token.as_str()),
+        };
+        let Some((field, value)) = body.split_once(':') else {
+            text.push(token);
+            continue;
+        };
+        let filter = match field.to_lowercase().as_str() {
+            "tap" => Filter::Tap(non_empty(field, value)?.to_lowercase()),
+            "license" => Filter::License(non_empty(field, value)?.to_lowercase()),
+            "installed" => Filter::Installed(parse_bool(field, value)?),
+            "deprecated" => Filter::Deprecated(parse_bool(field, value)?),
+            "dep" => Filter::Dep(non_empty(field, value)?.to_lowercase()),
+            _ => {
+                text.push(token);
+                continue;
+            }
+        };
+        query.filters.push(if negated { Filter::Not(Box::new(filter)) } else { filter });
+    }
+
+    query.text = text.join(" ");
+    Ok(query)
+}
+
+/// Splits on whitespace, keeping double-quoted runs (`tap:"a b"`) together
+/// and dropping the quotes.
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+    for ch in input.chars() {
+        match ch {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn non_empty<'a>(field: &str, value: &'a str) -> Result<&'a str, String> {
+    if value.is_empty() {
+        Err(format!("{field}: needs a value"))
+    } else {
+        Ok(value)
+    }
+}
+
+fn parse_bool(field: &str, value: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "yes" | "y" | "true" | "1" => Ok(true),
+        "no" | "n" | "false" | "0" => Ok(false),
+        _ => Err(format!("{field}: expects yes/no, got \"{value}\"")),
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, item: &dyn Filterable, installed: bool) -> bool {
+        match self {
+            Filter::Tap(tap) => {
+                let item_tap = item.tap().to_lowercase();
+                item_tap == *tap
+                    || (!tap.contains('/') && item_tap.split('/').next() == Some(tap.as_str()))
+            }
+            Filter::License(license) => item.license().is_some_and(|l| {
+                l.split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                    .any(|id| id.to_lowercase().starts_with(license.as_str()))
+            }),
+            Filter::Installed(want) => installed == *want,
+            Filter::Deprecated(want) => item.deprecated() == *want,
+            Filter::Dep(dep) => item.dependencies().iter().any(|d| {
+                // Third-party dependencies are listed as `user/tap/name`.
+                let short = d.rsplit('/').next().unwrap_or(d);
+                short.eq_ignore_ascii_case(dep) || d.eq_ignore_ascii_case(dep)
+            }),
+            Filter::Not(inner) => !inner.matches(item, installed),
+        }
+    }
+}
+
+impl Query {
+    pub fn matches(&self, item: &dyn Filterable, installed: bool) -> bool {
+        self.filters.iter().all(|f| f.matches(item, installed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formula(tap: &str, license: Option<&str>, deps: &[&str]) -> InstalledFormula {
+        serde_json::from_value(serde_json::json!({
+            "name": "example",
+            "full_name": "example",
+            "tap": tap,
+            "license": license,
+            "dependencies": deps,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_filters_negation_and_text() {
+        let query = parse(r#"json -tap:Homebrew/core installed:no dep:"openssl@3" parser"#).unwrap();
+        assert_eq!(query.text, "json parser");
+        assert_eq!(
+            query.filters,
+            [
+                Filter::Not(Box::new(Filter::Tap("homebrew/core".to_string()))),
+                Filter::Installed(false),
+                Filter::Dep("openssl@3".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_fields_and_plain_dashes_as_text() {
+        let query = parse("c++ -v http:server -foo:bar").unwrap();
+        assert_eq!(query.text, "c++ -v http:server -foo:bar");
+        assert!(query.filters.is_empty());
+    }
+
+    #[test]
+    fn rejects_missing_and_malformed_values() {
+        assert_eq!(parse("tap:").unwrap_err(), "tap: needs a value");
+        assert_eq!(parse("-license:").unwrap_err(), "license: needs a value");
+        assert_eq!(parse("installed:maybe").unwrap_err(), "installed: expects yes/no, got \"maybe\"");
+        assert_eq!(parse("Deprecated:Y").unwrap().filters, [Filter::Deprecated(true)]);
+    }
+
+    #[test]
+    fn tap_matches_whole_name_or_user() {
+        let item = formula("homebrew/core", None, &[]);
+        assert!(Filter::Tap("homebrew/core".to_string()).matches(&item, false));
+        assert!(Filter::Tap("homebrew".to_string()).matches(&item, false));
+        assert!(!Filter::Tap("homebrew/cask".to_string()).matches(&item, false));
+        assert!(!Filter::Tap("home".to_string()).matches(&item, false));
+    }
+
+    #[test]
+    fn license_matches_each_identifier_by_prefix() {
+        let item = formula("homebrew/core", Some("(MIT OR GPL-2.0-only)"), &[]);
+        assert!(parse("license:gpl").unwrap().matches(&item, false));
+        assert!(parse("license:mit").unwrap().matches(&item, false));
+        assert!(!parse("-license:gpl").unwrap().matches(&item, false));
+        assert!(!parse("license:apache").unwrap().matches(&item, false));
+        assert!(!parse("license:mit").unwrap().matches(&formula("homebrew/core", None, &[]), false));
+    }
+
+    #[test]
+    fn dep_matches_short_and_qualified_names() {
+        let item = formula("homebrew/core", None, &["openssl@3", "someone/tools/libfoo"]);
+        assert!(parse("dep:OpenSSL@3").unwrap().matches(&item, false));
+        assert!(parse("dep:libfoo").unwrap().matches(&item, false));
+        assert!(parse("dep:someone/tools/libfoo").unwrap().matches(&item, false));
+        assert!(!parse("dep:openssl").unwrap().matches(&item, false));
+    }
+
+    #[test]
+    fn filters_are_anded() {
+        let item = formula("homebrew/core", Some("MIT"), &[]);
+        assert!(parse("installed:yes tap:homebrew").unwrap().matches(&item, true));
+        assert!(!parse("installed:yes tap:homebrew").unwrap().matches(&item, false));
+    }
+}
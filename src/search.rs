@@ -0,0 +1,328 @@
+use crate::brew::types::{CaskInfo, FormulaInfo, InstalledCask, InstalledFormula, OutdatedPackage, ServiceInfo, TapInfo};
+
+/// A successful fuzzy match of a query against a candidate string.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i32,
+    /// Character (not byte) indices into the candidate that matched.
+    pub positions: Vec<usize>,
+}
+
+const SCORE_MATCH: i32 = 16;
+const BONUS_BOUNDARY: i32 = 8;
+const BONUS_FIRST_CHAR: i32 = 4;
+const BONUS_CONSECUTIVE: i32 = 8;
+const PENALTY_GAP_START: i32 = 3;
+const PENALTY_GAP_EXTENSION: i32 = 1;
+const BONUS_PREFIX: i32 = 32;
+const BONUS_EXACT: i32 = 64;
+
+fn is_separator(c: char) -> bool {
+    matches!(c, '-' | '_' | '@' | '/' | '.' | ' ' | '+')
+}
+
+/// Matches `query` as a case-insensitive subsequence of `candidate` and
+/// scores the best alignment. Exact and prefix matches score highest, then
+/// matches on word boundaries (after `-`, `@`, `/`, ...) and consecutive
+/// runs; gaps between matched characters cost a little. An empty query
+/// matches everything with a score of zero.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let q: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if q.is_empty() {
+        return Some(FuzzyMatch::default());
+    }
+    // Lowercase per char so indices stay aligned with `candidate.chars()`.
+    let c: Vec<char> = candidate
+        .chars()
+        .map(|ch| ch.to_lowercase().next().unwrap_or(ch))
+        .collect();
+    let (m, n) = (q.len(), c.len());
+    if m > n {
+        return None;
+    }
+
+    // Cheap rejection before the quadratic part.
+    let mut qi = 0;
+    for &ch in &c {
+        if qi < m && ch == q[qi] {
+            qi += 1;
+        }
+    }
+    if qi < m {
+        return None;
+    }
+
+    const NONE: i32 = i32::MIN / 2;
+    // best[i][j]: best score with q[i] matched at c[j]; from[i][j]: where
+    // q[i - 1] was matched on that path.
+    let mut best = vec![vec![NONE; n]; m];
+    let mut from = vec![vec![0usize; n]; m];
+
+    for i in 0..m {
+        // Running max of best[i-1][k] + k over k <= j - 2, for the affine
+        // gap penalty: gap(j - k - 1) = START + (j - k - 2) * EXTENSION.
+        let mut gap_best = NONE;
+        let mut gap_from = 0;
+        for j in i..n {
+            if i > 0 && j >= 2 && best[i - 1][j - 2] != NONE {
+                let v = best[i - 1][j - 2] + (j as i32 - 2) * PENALTY_GAP_EXTENSION;
+                if v > gap_best {
+                    gap_best = v;
+                    gap_from = j - 2;
+                }
+            }
+            if c[j] != q[i] {
+                continue;
+            }
+
+            let mut score = SCORE_MATCH;
+            if j == 0 || is_separator(c[j - 1]) {
+                score += BONUS_BOUNDARY;
+            }
+            if j == 0 {
+                score += BONUS_FIRST_CHAR;
+            }
+
+            if i == 0 {
+                best[i][j] = score;
+                continue;
+            }
+
+            let mut prev = NONE;
+            if best[i - 1][j - 1] != NONE {
+                prev = best[i - 1][j - 1] + BONUS_CONSECUTIVE;
+                from[i][j] = j - 1;
+            }
+            if gap_best != NONE {
+                let v = gap_best - (j as i32 - 2) * PENALTY_GAP_EXTENSION - PENALTY_GAP_START;
+                if v > prev {
+                    prev = v;
+                    from[i][j] = gap_from;
+                }
+            }
+            if prev != NONE {
+                best[i][j] = prev + score;
+            }
+        }
+    }
+
+    let (end, mut score) = (0..n)
+        .filter(|&j| best[m - 1][j] != NONE)
+        .map(|j| (j, best[m - 1][j]))
+        .max_by_key(|&(j, s)| (s, std::cmp::Reverse(j)))?;
+
+    let mut positions = vec![0; m];
+    let mut j = end;
+    for i in (0..m).rev() {
+        positions[i] = j;
+        if i > 0 {
+            j = from[i][j];
+        }
+    }
+
+    if positions.iter().enumerate().all(|(i, &p)| i == p) {
+        score += BONUS_PREFIX;
+        if m == n {
+            score += BONUS_EXACT;
+        }
+    }
+
+    Some(FuzzyMatch { score, positions })
+}
+
+/// What a search query is matched against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchScope {
+    /// Fuzzy match on the package name only.
+    #[default]
+    Name,
+    /// Also match descriptions, aliases, old names, taps and display names.
+    FullText,
+}
+
+impl SearchScope {
+    pub fn toggle(self) -> Self {
+        match self {
+            SearchScope::Name => SearchScope::FullText,
+            SearchScope::FullText => SearchScope::Name,
+        }
+    }
+}
+
+/// A package that can be found by search.
+pub trait Searchable {
+    /// The name shown in the list and fuzzy-matched in every scope.
+    fn search_name(&self) -> &str;
+    /// Extra text considered by `SearchScope::FullText`.
+    fn search_fields(&self) -> Vec<&str>;
+}
+
+impl Searchable for FormulaInfo {
+    fn search_name(&self) -> &str {
+        &self.name
+    }
+
+    fn search_fields(&self) -> Vec<&str> {
+        let mut fields = vec![self.full_name.as_str(), &self.desc, &self.tap];
+        fields.extend(self.aliases.iter().map(String::as_str));
+        fields.extend(self.oldnames.iter().map(String::as_str));
+        fields
+    }
+}
+
+impl Searchable for CaskInfo {
+    fn search_name(&self) -> &str {
+        &self.token
+    }
+
+    fn search_fields(&self) -> Vec<&str> {
+        let mut fields = vec![self.desc.as_str(), &self.tap];
+        fields.extend(self.name.iter().map(String::as_str));
+        fields.extend(self.old_tokens.iter().map(String::as_str));
+        fields
+    }
+}
+
+impl Searchable for InstalledFormula {
+    fn search_name(&self) -> &str {
+        &self.name
+    }
+
+    fn search_fields(&self) -> Vec<&str> {
+        let mut fields = vec![self.full_name.as_str(), &self.desc, &self.tap];
+        fields.extend(self.aliases.iter().map(String::as_str));
+        fields.extend(self.oldnames.iter().map(String::as_str));
+        fields
+    }
+}
+
+impl Searchable for InstalledCask {
+    fn search_name(&self) -> &str {
+        &self.token
+    }
+
+    fn search_fields(&self) -> Vec<&str> {
+        let mut fields = vec![self.desc.as_str(), &self.tap];
+        fields.extend(self.name.iter().map(String::as_str));
+        fields
+    }
+}
+
+impl Searchable for OutdatedPackage {
+    fn search_name(&self) -> &str {
+        &self.name
+    }
+
+    fn search_fields(&self) -> Vec<&str> {
+        vec![]
+    }
+}
+
+impl Searchable for TapInfo {
+    fn search_name(&self) -> &str {
+        &self.name
+    }
+
+    fn search_fields(&self) -> Vec<&str> {
+        self.remote.as_deref().into_iter().collect()
+    }
+}
+
+impl Searchable for ServiceInfo {
+    fn search_name(&self) -> &str {
+        &self.name
+    }
+
+    fn search_fields(&self) -> Vec<&str> {
+        vec![self.status.as_str()]
+    }
+}
+
+/// Score for a query that equals an alias, old name or display name.
+const SCORE_FIELD_EXACT: i32 = 100;
+/// Score for a query whose words all appear somewhere in the package text.
+/// Lower than any fuzzy name match, so those always rank first.
+const SCORE_FIELD_WORDS: i32 = 1;
+
+/// Matches `query` against `item` in the given scope. Full-text matches that
+/// don't come from the name carry no highlight positions.
+pub fn search_match<T: Searchable>(query: &str, item: &T, scope: SearchScope) -> Option<FuzzyMatch> {
+    let name_match = fuzzy_match(query, item.search_name());
+    if scope == SearchScope::Name || name_match.is_some() {
+        return name_match;
+    }
+
+    let query = query.to_lowercase();
+    let fields: Vec<String> = item.search_fields().iter().map(|f| f.to_lowercase()).collect();
+    if fields.contains(&query) {
+        return Some(FuzzyMatch { score: SCORE_FIELD_EXACT, positions: vec![] });
+    }
+    let name = item.search_name().to_lowercase();
+    let found = query
+        .split_whitespace()
+        .all(|word| name.contains(word) || fields.iter().any(|f| f.contains(word)));
+    found.then(|| FuzzyMatch { score: SCORE_FIELD_WORDS, positions: vec![] })
+}
+
+/// Filters `items` by matching `query` in `scope` and sorts the matches
+/// best-first. Ties go to the shorter name, then to the original order, so
+/// an empty query leaves the list untouched.
+pub fn rank<'a, T: Searchable + 'a>(
+    items: impl IntoIterator<Item = &'a T>,
+    query: &str,
+    scope: SearchScope,
+) -> Vec<(&'a T, FuzzyMatch)> {
+    let mut matches: Vec<(&T, FuzzyMatch)> = items
+        .into_iter()
+        .filter_map(|item| search_match(query, item, scope).map(|m| (item, m)))
+        .collect();
+    if !query.is_empty() {
+        matches.sort_by_key(|(item, m)| {
+            (std::cmp::Reverse(m.score), item.search_name().chars().count())
+        });
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(query: &str, candidate: &str) -> Option<Vec<usize>> {
+        fuzzy_match(query, candidate).map(|m| m.positions)
+    }
+
+    #[test]
+    fn positions_prefer_word_boundaries_and_runs() {
+        assert_eq!(positions("ssl", "openssl@3").unwrap(), [4, 5, 6]);
+        assert_eq!(positions("gc", "gnu-coreutils").unwrap(), [0, 4]);
+        assert_eq!(positions("pyth", "python@3.12").unwrap(), [0, 1, 2, 3]);
+        assert_eq!(positions("ab", "xaxbab").unwrap(), [4, 5]);
+    }
+
+    #[test]
+    fn positions_are_char_indices() {
+        assert_eq!(positions("é", "café").unwrap(), [3]);
+        assert_eq!(positions("CAF", "Café").unwrap(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn rejects_non_subsequences() {
+        assert_eq!(positions("zz", "jq"), None);
+        assert_eq!(positions("qj", "jq"), None);
+        assert_eq!(positions("jqq", "jq"), None);
+    }
+
+    #[test]
+    fn empty_query_matches_with_no_positions() {
+        assert_eq!(fuzzy_match("", "jq"), Some(FuzzyMatch::default()));
+    }
+
+    #[test]
+    fn exact_beats_prefix_beats_boundary() {
+        let score = |candidate| fuzzy_match("node", candidate).unwrap().score;
+        assert!(score("node") > score("node@20"));
+        assert!(score("node@20") > score("libnode-x"));
+        assert!(score("corepack-node") > score("xnoxdxe"));
+    }
+}
@@ -0,0 +1,237 @@
+use std::cmp::Ordering;
+
+use crate::brew::types::{
+    CaskInfo, FormulaInfo, InstalledCask, InstalledFormula, OutdatedPackage, PackageKind,
+};
+use crate::search::Searchable;
+
+/// What a package list is ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// brew/catalog order, or best match first while searching.
+    #[default]
+    Default,
+    Name,
+    InstallDate,
+    Size,
+    Popularity,
+    Version,
+}
+
+impl SortKey {
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Default => SortKey::Name,
+            SortKey::Name => SortKey::InstallDate,
+            SortKey::InstallDate => SortKey::Size,
+            SortKey::Size => SortKey::Popularity,
+            SortKey::Popularity => SortKey::Version,
+            SortKey::Version => SortKey::Default,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Default => "default",
+            SortKey::Name => "name",
+            SortKey::InstallDate => "installed",
+            SortKey::Size => "size",
+            SortKey::Popularity => "popularity",
+            SortKey::Version => "version",
+        }
+    }
+}
+
+/// A package that can be placed in a sorted list. Install dates, sizes and
+/// popularity are looked up by kind and name, since they live outside the
+/// package records.
+pub trait Sortable: Searchable {
+    fn kind(&self) -> PackageKind;
+    fn version(&self) -> Option<&str>;
+}
+
+impl Sortable for FormulaInfo {
+    fn kind(&self) -> PackageKind {
+        PackageKind::Formula
+    }
+
+    fn version(&self) -> Option<&str> {
+        self.versions.stable.as_deref()
+    }
+}
+
+impl Sortable for CaskInfo {
+    fn kind(&self) -> PackageKind {
+        PackageKind::Cask
+    }
+
+    fn version(&self) -> Option<&str> {
+        Some(&self.version)
+    }
+}
+
+impl Sortable for InstalledFormula {
+    fn kind(&self) -> PackageKind {
+        PackageKind::Formula
+    }
+
+    fn version(&self) -> Option<&str> {
+        self.latest_keg().map(|k| k.version.as_str())
+    }
+}
+
+impl Sortable for InstalledCask {
+    fn kind(&self) -> PackageKind {
+        PackageKind::Cask
+    }
+
+    fn version(&self) -> Option<&str> {
+        Some(self.installed.as_deref().unwrap_or(&self.version))
+    }
+}
+
+impl Sortable for OutdatedPackage {
+    fn kind(&self) -> PackageKind {
+        self.kind
+    }
+
+    fn version(&self) -> Option<&str> {
+        Some(&self.current_version)
+    }
+}
+
+/// Stable-sorts `items` by `key`. Items without a key always go last, in
+/// either direction, so unknown sizes don't crowd the top of the list.
+pub fn sort_by_key<T, K: Ord>(items: &mut Vec<T>, descending: bool, key: impl Fn(&T) -> Option<K>) {
+    let mut keyed: Vec<(Option<K>, T)> = items.drain(..).map(|item| (key(&item), item)).collect();
+    keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(a),
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+    items.extend(keyed.into_iter().map(|(_, item)| item));
+}
+
+/// A version string ordered the way people read versions rather than
+/// lexically, so `1.10` sorts after `1.9`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version<'a>(pub &'a str);
+
+impl Ord for Version<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_versions(self.0, other.0)
+    }
+}
+
+impl PartialOrd for Version<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compares versions piece by piece, where pieces are runs of digits
+/// (compared numerically) or letters. Punctuation only separates pieces, so
+/// brew revisions like `1.2_1` sort after `1.2`. A trailing letter piece
+/// marks a pre-release: `1.0rc1` sorts before `1.0`.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a, b) = (pieces(a), pieces(b));
+    for i in 0..a.len().max(b.len()) {
+        let ord = match (a.get(i), b.get(i)) {
+            (Some(x), Some(y)) => compare_piece(x, y),
+            (Some(x), None) => extra_piece(x),
+            (None, Some(y)) => extra_piece(y).reverse(),
+            (None, None) => Ordering::Equal,
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    Ordering::Equal
+}
+
+fn pieces(v: &str) -> Vec<&str> {
+    let mut out = vec![];
+    let mut start = None;
+    let mut digits = false;
+    for (i, c) in v.char_indices() {
+        let piece_char = c.is_ascii_alphanumeric();
+        match start {
+            Some(s) if !piece_char || c.is_ascii_digit() != digits => {
+                out.push(&v[s..i]);
+                start = piece_char.then_some(i);
+            }
+            None if piece_char => start = Some(i),
+            _ => {}
+        }
+        digits = c.is_ascii_digit();
+    }
+    if let Some(s) = start {
+        out.push(&v[s..]);
+    }
+    out
+}
+
+fn is_numeric(piece: &str) -> bool {
+    piece.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn compare_piece(a: &str, b: &str) -> Ordering {
+    match (is_numeric(a), is_numeric(b)) {
+        (true, true) => {
+            // Compare by length first so long numbers never overflow.
+            let (a, b) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+            a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+        }
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+/// How a version with one more piece compares to the shorter one.
+fn extra_piece(piece: &str) -> Ordering {
+    if is_numeric(piece) {
+        Ordering::Greater
+    } else {
+        Ordering::Less
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_numbers_numerically() {
+        assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.02", "1.2"), Ordering::Equal);
+        assert_eq!(compare_versions("2", "10"), Ordering::Less);
+        assert_eq!(compare_versions("123456789012345678901234567890", "9"), Ordering::Greater);
+    }
+
+    #[test]
+    fn revisions_sort_after_and_prereleases_before() {
+        assert_eq!(compare_versions("1.2_1", "1.2"), Ordering::Greater);
+        assert_eq!(compare_versions("1.2.1", "1.2_1"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0rc1", "1.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0beta", "1.0rc"), Ordering::Less);
+        assert_eq!(compare_versions("1.0a", "1.0.1"), Ordering::Less);
+    }
+
+    #[test]
+    fn letters_ignore_case_and_sort_below_numbers() {
+        assert_eq!(compare_versions("1.0RC1", "1.0rc1"), Ordering::Equal);
+        assert_eq!(compare_versions("HEAD-abc", "1.0"), Ordering::Less);
+    }
+
+    #[test]
+    fn sort_by_key_keeps_missing_keys_last() {
+        let mut items = vec![("a", None), ("b", Some(2)), ("c", Some(10)), ("d", None)];
+        sort_by_key(&mut items, true, |(_, k)| *k);
+        assert_eq!(items.iter().map(|(n, _)| *n).collect::<Vec<_>>(), ["c", "b", "a", "d"]);
+        sort_by_key(&mut items, false, |(_, k)| *k);
+        assert_eq!(items.iter().map(|(n, _)| *n).collect::<Vec<_>>(), ["b", "c", "a", "d"]);
+    }
+}
@@ -0,0 +1,82 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::App;
+use crate::brewfile::{Entry, RestartService};
+
+use super::dialog::centered_rect;
+
+pub fn render_brewfile_plan(f: &mut Frame, area: Rect, app: &App) {
+    let Some(brewfile) = &app.brewfile else {
+        return;
+    };
+    let popup = centered_rect(area.width.saturating_sub(8), area.height.saturating_sub(4), area);
+    f.render_widget(Clear, popup);
+
+    let mut lines = vec![];
+    let title = match app.brewfile_plan() {
+        Some(plan) => {
+            let heading = |text: String, color: Color| {
+                Line::from(Span::styled(text, Style::default().fg(color).add_modifier(Modifier::BOLD)))
+            };
+            lines.push(heading(format!("To install ({})", plan.install.len()), Color::Green));
+            lines.extend(plan.install.iter().map(|e| entry_line("+", e, Style::default().fg(Color::Green))));
+            lines.push(Line::from(""));
+            lines.push(heading(format!("To remove with [s] sync ({})", plan.remove.len()), Color::Red));
+            lines.extend(plan.remove.iter().map(|(kind, name)| {
+                Line::from(Span::styled(format!("  - {} \"{name}\"", kind.as_str()), Style::default().fg(Color::Red)))
+            }));
+            lines.push(Line::from(""));
+            lines.push(heading(format!("Already satisfied ({})", plan.satisfied.len()), Color::DarkGray));
+            lines.extend(plan.satisfied.iter().map(|e| entry_line("✓", e, Style::default().fg(Color::DarkGray))));
+            format!(
+                "Brewfile {}: {} to install, {} to remove, {} satisfied",
+                brewfile.path.display(),
+                plan.install.len(),
+                plan.remove.len(),
+                plan.satisfied.len()
+            )
+        }
+        None => {
+            lines.push(Line::from("Loading installed packages and taps…"));
+            format!("Brewfile {}", brewfile.path.display())
+        }
+    };
+    if !brewfile.ignored.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("Ignored (not managed by taphouse, or conditional): {}", brewfile.ignored.join(", ")),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .style(Style::default().bg(Color::Black));
+    let para = Paragraph::new(lines).block(block).scroll((app.brewfile_scroll, 0));
+    f.render_widget(para, popup);
+}
+
+fn entry_line<'a>(marker: &str, entry: &'a Entry, style: Style) -> Line<'a> {
+    let mut spans = vec![Span::styled(format!("  {marker} {} \"{}\"", entry.kind.as_str(), entry.name), style)];
+    if let Some(url) = &entry.url {
+        spans.push(Span::styled(format!(" {url}"), Style::default().fg(Color::DarkGray)));
+    }
+    if !entry.args.is_empty() {
+        spans.push(Span::styled(format!(" {}", entry.args.join(" ")), Style::default().fg(Color::Cyan)));
+    }
+    match entry.restart_service {
+        RestartService::Always => spans.push(Span::styled("  ⟳ restart service", Style::default().fg(Color::Yellow))),
+        RestartService::Changed => {
+            spans.push(Span::styled("  ⟳ restart service if changed", Style::default().fg(Color::Yellow)))
+        }
+        RestartService::Never => {}
+    }
+    Line::from(spans)
+}
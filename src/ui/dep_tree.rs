@@ -0,0 +1,68 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem},
+    Frame,
+};
+
+use crate::app::App;
+use crate::brew::types::RemoteData;
+
+use super::dialog::centered_rect;
+
+pub fn render_dep_tree(f: &mut Frame, area: Rect, app: &App) {
+    let popup = centered_rect(area.width.saturating_sub(8), area.height.saturating_sub(4), area);
+    f.render_widget(Clear, popup);
+
+    let graph = app.dep_graph();
+    let tree = &app.dep_tree;
+    let rows = tree.rows(&graph);
+
+    let items: Vec<ListItem> = rows
+        .iter()
+        .map(|row| {
+            let marker = if row.has_children {
+                if row.expanded { "▾ " } else { "▸ " }
+            } else {
+                "  "
+            };
+            let mut spans = vec![
+                Span::raw("  ".repeat(row.depth())),
+                Span::raw(marker),
+                Span::raw(row.name.clone()),
+            ];
+            if row.installed {
+                spans.push(Span::styled(" ✓", Style::default().fg(Color::Green)));
+            }
+            if row.cycle {
+                spans.push(Span::styled(" ↻ cycle", Style::default().fg(Color::Red)));
+            } else if row.unknown {
+                spans.push(Span::styled(" (not in catalog)", Style::default().fg(Color::DarkGray)));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let all = graph.closure(&tree.root);
+    let missing = all.iter().filter(|name| !graph.is_installed(name)).count();
+    let loading = match app.browse_formulae {
+        RemoteData::Loading => " (loading catalog…)",
+        _ => "",
+    };
+    let title = format!(
+        "Dependencies of {}: {} total, {missing} not installed{loading}",
+        tree.root,
+        all.len()
+    );
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .style(Style::default().bg(Color::Black));
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED));
+    let mut ls = tree.state.clone();
+    f.render_stateful_widget(list, popup, &mut ls);
+}
@@ -7,7 +7,10 @@ use ratatui::{
 };
 
 use crate::app::{App, Tab};
-use crate::brew::types::RemoteData;
+use crate::brew::types::{PackageKind, RemoteData};
+use crate::manifest::{Outcome, Rule};
+
+use super::package_list::{outcome_span, service_status_style};
 
 pub fn render_detail(f: &mut Frame, area: Rect, app: &App) {
     let block = Block::default().borders(Borders::ALL).title("Detail");
@@ -15,9 +18,9 @@ pub fn render_detail(f: &mut Frame, area: Rect, app: &App) {
     match app.tab {
         Tab::BrowseFormulae => {
             if let Some(info) = app.selected_formula_info() {
-                let installed = app.installed_formulae.contains(&info.name);
+                let installed = app.is_formula_installed(&info.name);
                 let version = info.versions.stable.as_deref().unwrap_or("?");
-                let lines = vec![
+                let mut lines = vec![
                     Line::from(vec![
                         Span::styled("Name:    ", Style::default().add_modifier(Modifier::BOLD)),
                         Span::raw(&info.name),
@@ -36,7 +39,7 @@ pub fn render_detail(f: &mut Frame, area: Rect, app: &App) {
                     ]),
                     Line::from(vec![
                         Span::styled("Tap:     ", Style::default().add_modifier(Modifier::BOLD)),
-                        Span::raw(&info.tap),
+                        Span::raw(tap_label(&info.tap, info.local_tap)),
                     ]),
                     Line::from(vec![
                         Span::styled("Deps:    ", Style::default().add_modifier(Modifier::BOLD)),
@@ -50,13 +53,14 @@ pub fn render_detail(f: &mut Frame, area: Rect, app: &App) {
                         Span::styled("Homepage:", Style::default().add_modifier(Modifier::BOLD)),
                         Span::raw(format!(" {}", info.homepage)),
                     ]),
-                    Line::from(""),
-                    Line::from(if installed {
-                        Span::styled("✓ Installed", Style::default().fg(Color::Green))
-                    } else {
-                        Span::styled("[i] to install", Style::default().fg(Color::Cyan))
-                    }),
                 ];
+                lines.extend(used_by_lines(app, PackageKind::Formula, &info.name));
+                lines.push(Line::from(""));
+                lines.push(Line::from(if installed {
+                    Span::styled("✓ Installed", Style::default().fg(Color::Green))
+                } else {
+                    Span::styled("[i] to install", Style::default().fg(Color::Cyan))
+                }));
                 let para = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
                 f.render_widget(para, area);
             } else {
@@ -78,9 +82,9 @@ pub fn render_detail(f: &mut Frame, area: Rect, app: &App) {
         }
         Tab::BrowseCasks => {
             if let Some(info) = app.selected_cask_info() {
-                let installed = app.installed_casks.contains(&info.token);
+                let installed = app.is_cask_installed(&info.token);
                 let display_name = info.name.first().map(|s| s.as_str()).unwrap_or(&info.token);
-                let lines = vec![
+                let mut lines = vec![
                     Line::from(vec![
                         Span::styled("Name:    ", Style::default().add_modifier(Modifier::BOLD)),
                         Span::raw(display_name),
@@ -97,17 +101,22 @@ pub fn render_detail(f: &mut Frame, area: Rect, app: &App) {
                         Span::styled("Version: ", Style::default().add_modifier(Modifier::BOLD)),
                         Span::raw(&info.version),
                     ]),
+                    Line::from(vec![
+                        Span::styled("Tap:     ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(tap_label(&info.tap, info.local_tap)),
+                    ]),
                     Line::from(vec![
                         Span::styled("Homepage:", Style::default().add_modifier(Modifier::BOLD)),
                         Span::raw(format!(" {}", info.homepage)),
                     ]),
-                    Line::from(""),
-                    Line::from(if installed {
-                        Span::styled("✓ Installed", Style::default().fg(Color::Green))
-                    } else {
-                        Span::styled("[i] to install", Style::default().fg(Color::Cyan))
-                    }),
                 ];
+                lines.extend(used_by_lines(app, PackageKind::Cask, &info.token));
+                lines.push(Line::from(""));
+                lines.push(Line::from(if installed {
+                    Span::styled("✓ Installed", Style::default().fg(Color::Green))
+                } else {
+                    Span::styled("[i] to install", Style::default().fg(Color::Cyan))
+                }));
                 let para = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
                 f.render_widget(para, area);
             } else {
@@ -127,17 +136,324 @@ pub fn render_detail(f: &mut Frame, area: Rect, app: &App) {
                 }
             }
         }
-        Tab::InstalledFormulae | Tab::InstalledCasks => {
-            if let Some(name) = app.selected_name() {
+        Tab::Outdated => {
+            if let Some(pkg) = app.selected_outdated() {
+                let kind = match pkg.kind {
+                    PackageKind::Formula => "formula",
+                    PackageKind::Cask => "cask",
+                };
+                let mut lines = vec![
+                    Line::from(vec![
+                        Span::styled("Name:      ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(&pkg.name),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Kind:      ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(kind),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Installed: ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(pkg.installed_versions.join(", ")),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Latest:    ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(&pkg.current_version),
+                    ]),
+                ];
+                if pkg.pinned {
+                    lines.push(Line::from(vec![
+                        Span::styled("Pinned:    ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(pkg.pinned_version.as_deref().unwrap_or("yes")),
+                    ]));
+                }
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled("[U] Upgrade", Style::default().fg(Color::Cyan))));
+                let para = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+                f.render_widget(para, area);
+            } else {
+                let msg = match &app.outdated {
+                    RemoteData::Loading => "Checking for outdated packages…".to_string(),
+                    RemoteData::Failed(e) => format!("Error: {e}"),
+                    _ => "Everything is up to date".to_string(),
+                };
+                let para = Paragraph::new(msg).block(block);
+                f.render_widget(para, area);
+            }
+        }
+        Tab::InstalledFormulae | Tab::Leaves => {
+            if let Some(info) = app.selected_installed_formula() {
+                let versions = info.installed_versions().join(", ");
+                let keg = info.latest_keg();
+                let mut lines = vec![
+                    Line::from(vec![
+                        Span::styled("Name:       ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(&info.name),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Desc:       ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(&info.desc),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Installed:  ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(versions),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Linked:     ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(info.linked_keg.as_deref().unwrap_or("not linked")),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Pinned:     ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(yes_no(info.pinned)),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("On request: ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(if info.installed_on_request() { "yes" } else { "no (dependency)" }),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Bottle:     ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(match keg {
+                            Some(k) if k.poured_from_bottle => "poured from bottle",
+                            Some(_) => "built from source",
+                            None => "unknown",
+                        }),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Keg-only:   ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(yes_no(info.keg_only)),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Date:       ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(info.install_time().map(format_date).unwrap_or_else(|| "unknown".to_string())),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Tap:        ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(&info.tap),
+                    ]),
+                ];
+                if let RemoteData::Loaded(sizes) = &app.disk_usage {
+                    if let Some(bytes) = sizes.get(PackageKind::Formula, &info.name) {
+                        lines.push(Line::from(vec![
+                            Span::styled("Size:       ", Style::default().add_modifier(Modifier::BOLD)),
+                            Span::raw(format_size(bytes)),
+                        ]));
+                    }
+                }
+                lines.extend(used_by_lines(app, PackageKind::Formula, &info.name));
+                if info.outdated {
+                    lines.push(Line::from(Span::styled("⚠ Outdated", Style::default().fg(Color::Yellow))));
+                }
+                lines.push(Line::from(""));
+                let pin = if info.pinned { "[P] Unpin" } else { "[p] Pin" };
+                lines.push(Line::from(Span::styled(
+                    format!("[u] Uninstall  [U] Upgrade  {pin}"),
+                    Style::default().fg(Color::Cyan),
+                )));
+                let para = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+                f.render_widget(para, area);
+            } else {
+                let para = Paragraph::new("No packages installed").block(block);
+                f.render_widget(para, area);
+            }
+        }
+        Tab::Compliance => {
+            let Some(manifest) = &app.manifest else {
+                let para = Paragraph::new(
+                    "No team manifest loaded. Pass --manifest PATH or set `manifest` in the config file.",
+                )
+                .block(block)
+                .wrap(Wrap { trim: true });
+                f.render_widget(para, area);
+                return;
+            };
+            let checks = &app.compliance;
+            let count = |outcome: Outcome| checks.iter().filter(|c| c.outcome == outcome).count();
+            let mut lines = vec![
+                Line::from(vec![
+                    Span::styled("Manifest: ", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(manifest.path.display().to_string()),
+                ]),
+                Line::from(vec![
+                    Span::styled("Summary:  ", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::styled(format!("{} passed", count(Outcome::Pass)), Style::default().fg(Color::Green)),
+                    Span::raw(", "),
+                    Span::styled(format!("{} failed", count(Outcome::Fail)), Style::default().fg(Color::Red)),
+                    Span::raw(format!(", {} skipped", count(Outcome::Skipped))),
+                ]),
+                Line::from(""),
+            ];
+            if let Some(check) = app.selected_check() {
+                let rule = match &check.rule {
+                    Rule::Required => "Required".to_string(),
+                    Rule::Optional { group } => format!("Optional (group {group})"),
+                    Rule::Forbidden => "Forbidden".to_string(),
+                    Rule::MinimumVersion { minimum } => format!("Minimum version {minimum}"),
+                };
+                lines.extend([
+                    Line::from(vec![
+                        Span::styled("Rule:     ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(rule),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Package:  ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(format!("{} {}", check.kind.as_str(), check.name)),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Result:   ", Style::default().add_modifier(Modifier::BOLD)),
+                        outcome_span(check.outcome),
+                        Span::raw(check.detail.clone()),
+                    ]),
+                ]);
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "[f] Fix selected  [F] Fix all failures",
+                Style::default().fg(Color::Cyan),
+            )));
+            let para = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+            f.render_widget(para, area);
+        }
+        Tab::Services => {
+            if let Some(service) = app.selected_service() {
+                let lines = vec![
+                    Line::from(vec![
+                        Span::styled("Name:      ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(&service.name),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Status:    ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::styled(&service.status, service_status_style(&service.status)),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("User:      ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(service.user.as_deref().unwrap_or("—")),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("PID:       ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(service.pid.map(|p| p.to_string()).unwrap_or_else(|| "—".to_string())),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Exit code: ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(service.exit_code.map(|c| c.to_string()).unwrap_or_else(|| "—".to_string())),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("File:      ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(service.file.as_deref().unwrap_or("—")),
+                    ]),
+                    Line::from(""),
+                    Line::from(Span::styled(
+                        "[s] Start  [x] Stop  [R] Restart  [n] Run (without starting at login)",
+                        Style::default().fg(Color::Cyan),
+                    )),
+                ];
+                let para = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+                f.render_widget(para, area);
+            } else {
+                let msg = match &app.services {
+                    RemoteData::Loading => "Loading services…".to_string(),
+                    RemoteData::Failed(e) => format!("Error: {e}"),
+                    _ => "No services".to_string(),
+                };
+                let para = Paragraph::new(msg).block(block);
+                f.render_widget(para, area);
+            }
+        }
+        Tab::Taps => {
+            if let Some(tap) = app.selected_tap() {
                 let lines = vec![
                     Line::from(vec![
-                        Span::styled("Name: ", Style::default().add_modifier(Modifier::BOLD)),
-                        Span::raw(&name),
+                        Span::styled("Name:     ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(&tap.name),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Remote:   ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(tap.remote.as_deref().unwrap_or("none")),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Official: ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(yes_no(tap.official)),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Private:  ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(yes_no(tap.private)),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Formulae: ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(tap.formula_names.len().to_string()),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Casks:    ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(tap.cask_tokens.len().to_string()),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Path:     ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(&tap.path),
                     ]),
                     Line::from(""),
-                    Line::from(Span::styled("[u] Uninstall  [U] Upgrade", Style::default().fg(Color::Cyan))),
+                    Line::from(Span::styled(
+                        "[Enter] Browse contents  [u] Untap  [t] Add a tap",
+                        Style::default().fg(Color::Cyan),
+                    )),
                 ];
-                let para = Paragraph::new(lines).block(block);
+                let para = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+                f.render_widget(para, area);
+            } else {
+                let msg = match &app.taps {
+                    RemoteData::Loading => "Loading taps…".to_string(),
+                    RemoteData::Failed(e) => format!("Error: {e}"),
+                    _ => "No taps. Press [t] to add one.".to_string(),
+                };
+                let para = Paragraph::new(msg).block(block);
+                f.render_widget(para, area);
+            }
+        }
+        Tab::InstalledCasks => {
+            if let Some(info) = app.selected_installed_cask() {
+                let display_name = info.name.first().map(|s| s.as_str()).unwrap_or(&info.token);
+                let mut lines = vec![
+                    Line::from(vec![
+                        Span::styled("Name:         ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(display_name),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Token:        ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(&info.token),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Desc:         ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(&info.desc),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Version:      ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(info.installed.as_deref().unwrap_or(&info.version)),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Installed:    ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(info.installed_time.map(format_date).unwrap_or_else(|| "unknown".to_string())),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Auto-updates: ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(yes_no(info.auto_updates.unwrap_or(false))),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Tap:          ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(&info.tap),
+                    ]),
+                ];
+                if let RemoteData::Loaded(sizes) = &app.disk_usage {
+                    if let Some(bytes) = sizes.get(PackageKind::Cask, &info.token) {
+                        lines.push(Line::from(vec![
+                            Span::styled("Size:         ", Style::default().add_modifier(Modifier::BOLD)),
+                            Span::raw(format_size(bytes)),
+                        ]));
+                    }
+                }
+                lines.extend(used_by_lines(app, PackageKind::Cask, &info.token));
+                if info.outdated {
+                    lines.push(Line::from(Span::styled("⚠ Outdated", Style::default().fg(Color::Yellow))));
+                }
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled("[u] Uninstall  [U] Upgrade", Style::default().fg(Color::Cyan))));
+                let para = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
                 f.render_widget(para, area);
             } else {
                 let para = Paragraph::new("No packages installed").block(block);
@@ -146,3 +462,100 @@ pub fn render_detail(f: &mut Frame, area: Rect, app: &App) {
         }
     }
 }
+
+/// The most names listed per "Used by" section before summarising the rest.
+const USED_BY_LIMIT: usize = 12;
+
+/// "Used by" sections for a package, from the reverse-dependency index.
+fn used_by_lines(app: &App, kind: PackageKind, name: &str) -> Vec<Line<'static>> {
+    let index = &app.reverse_index;
+    let installed = index.used_by_installed(kind, name);
+    let (loaded, loading) = match kind {
+        PackageKind::Formula => (
+            matches!(app.browse_formulae, RemoteData::Loaded(_)),
+            matches!(app.browse_formulae, RemoteData::Loading),
+        ),
+        PackageKind::Cask => (
+            matches!(app.browse_casks, RemoteData::Loaded(_)),
+            matches!(app.browse_casks, RemoteData::Loading),
+        ),
+    };
+    let all = if loaded {
+        name_list(index.used_by_all(kind, name))
+    } else if loading {
+        "loading catalog…".to_string()
+    } else {
+        "unknown (catalog not loaded)".to_string()
+    };
+    let installed_style = if installed.is_empty() {
+        Style::default()
+    } else {
+        Style::default().fg(Color::Yellow)
+    };
+    vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Used by (installed): ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(name_list(installed), installed_style),
+        ]),
+        Line::from(vec![
+            Span::styled("Used by (all):       ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(all),
+        ]),
+    ]
+}
+
+fn name_list(names: &[String]) -> String {
+    if names.is_empty() {
+        return "none".to_string();
+    }
+    let shown = names[..names.len().min(USED_BY_LIMIT)].join(", ");
+    if names.len() > USED_BY_LIMIT {
+        format!("{shown} and {} more", names.len() - USED_BY_LIMIT)
+    } else {
+        shown
+    }
+}
+
+fn tap_label(tap: &str, local_tap: bool) -> String {
+    if local_tap {
+        format!("{tap} (local tap)")
+    } else {
+        tap.to_string()
+    }
+}
+
+fn yes_no(b: bool) -> &'static str {
+    if b { "yes" } else { "no" }
+}
+
+/// Formats a unix timestamp as a `YYYY-MM-DD` UTC date.
+fn format_date(ts: i64) -> String {
+    // Civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = ts.div_euclid(86_400) + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Formats a byte count with a binary unit, e.g. `12.3 MiB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
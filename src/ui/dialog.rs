@@ -2,11 +2,13 @@ use ratatui::{
     layout::{Alignment, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph},
     Frame,
 };
 
-use crate::app::BrewAction;
+use crate::app::{BrewAction, Dependents, ServiceCommand};
+use crate::brew::types::PackageKind;
+use crate::policy::Violation;
 
 /// Center a rect of `width x height` within `area`.
 pub fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
@@ -20,26 +22,143 @@ pub fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
     }
 }
 
-pub fn render_confirm(f: &mut Frame, area: Rect, action: &BrewAction) {
-    let popup = centered_rect(44, 7, area);
-    f.render_widget(Clear, popup);
+/// The most dependents named per package before summarising the rest.
+const DEPENDENTS_LIMIT: usize = 4;
+
+pub fn render_confirm(
+    f: &mut Frame,
+    area: Rect,
+    actions: &[BrewAction],
+    dependents: &[Dependents],
+    skipped: &[String],
+    denied: &[Violation],
+) {
+    let total: usize = actions.iter().map(|a| a.names().len()).sum();
+    let verb = match actions.first() {
+        Some(BrewAction::Install { .. }) => "Install",
+        Some(BrewAction::Uninstall { .. }) => "Uninstall",
+        Some(BrewAction::Upgrade { .. }) => "Upgrade",
+        Some(BrewAction::Pin { .. }) => "Pin",
+        Some(BrewAction::Unpin { .. }) => "Unpin",
+        Some(BrewAction::Tap { .. }) => "Tap",
+        Some(BrewAction::Untap { .. }) => "Untap",
+        Some(BrewAction::Service { command, .. }) => match command {
+            ServiceCommand::Start => "Start",
+            ServiceCommand::Stop => "Stop",
+            ServiceCommand::Restart => "Restart",
+            ServiceCommand::Run => "Run",
+        },
+        Some(BrewAction::Autoremove { .. }) => "Autoremove",
+        None if !skipped.is_empty() || !denied.is_empty() => "",
+        None => return,
+    };
 
-    let title = match action {
-        BrewAction::Install { name, .. } => format!("Install {}?", name),
-        BrewAction::Uninstall { name, .. } => format!("Uninstall {}?", name),
-        BrewAction::Upgrade { name, .. } => format!("Upgrade {}?", name),
+    // A Brewfile plan mixes taps, installs and removals.
+    let mixed = actions.iter().any(|a| a.verb() != actions[0].verb());
+    let title = match actions {
+        [] if skipped.is_empty() => "Blocked by policy".to_string(),
+        [] => "Nothing to upgrade".to_string(),
+        _ if mixed => format!("Apply {} changes?", actions.len()),
+        // The name may be followed by a URL.
+        [BrewAction::Tap { names }] => format!("{verb} {}?", names.join(" ")),
+        [action] if total == 1 => format!("{verb} {}?", action.names()[0]),
+        [BrewAction::Service { .. }] => format!("{verb} {total} services?"),
+        _ => format!("{verb} {total} packages?"),
     };
 
-    let text = vec![
+    let mut list = vec![];
+    if mixed {
+        for action in actions {
+            list.push(Line::from(format!("• {}", action.args().join(" "))));
+        }
+    } else if total > 1 {
+        for action in actions {
+            let suffix = match action.kind() {
+                PackageKind::Cask => " (cask)",
+                PackageKind::Formula => "",
+            };
+            for name in action.names() {
+                list.push(Line::from(format!("• {name}{suffix}")));
+            }
+        }
+    }
+    let blocked: Vec<Line> = denied
+        .iter()
+        .map(|violation| Line::from(format!("{}: {}", violation.name, violation.reason)))
+        .collect();
+    let needed: Vec<Line> = dependents
+        .iter()
+        .map(|d| {
+            let shown = d.used_by[..d.used_by.len().min(DEPENDENTS_LIMIT)].join(", ");
+            let more = d.used_by.len().saturating_sub(DEPENDENTS_LIMIT);
+            let used_by = if more > 0 { format!("{shown} and {more} more") } else { shown };
+            Line::from(format!("{} ← {used_by}", d.name))
+        })
+        .collect();
+
+    // Everything but the three lists: title, section headings, blank lines,
+    // the prompt and the borders. The lists share what's left, warnings
+    // first, so the prompt and what it overrides are always on screen.
+    let fixed = 3
+        + usize::from(!list.is_empty())
+        + if skipped.is_empty() { 0 } else { 2 }
+        + if blocked.is_empty() { 0 } else { 2 }
+        + if needed.is_empty() { 0 } else { 2 }
+        + 2
+        + 2;
+    let mut room = usize::from(area.height).saturating_sub(fixed);
+    let needed = capped(needed, &mut room);
+    let blocked = capped(blocked, &mut room);
+    let list = capped(list, &mut room);
+
+    let mut text = vec![
         Line::from(""),
-        Line::from(Span::styled(&title, Style::default().add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(title, Style::default().add_modifier(Modifier::BOLD))),
         Line::from(""),
-        Line::from(vec![
+    ];
+    if !list.is_empty() {
+        text.extend(list);
+        text.push(Line::from(""));
+    }
+    if !skipped.is_empty() {
+        text.push(Line::from(Span::styled(
+            format!("📌 Skipping pinned: {}", skipped.join(", ")),
+            Style::default().fg(Color::Magenta),
+        )));
+        text.push(Line::from(""));
+    }
+    if !blocked.is_empty() {
+        text.push(Line::from(Span::styled(
+            "⛔ Blocked by policy:",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )));
+        text.extend(blocked);
+        text.push(Line::from(""));
+    }
+    if actions.is_empty() {
+        text.push(Line::from(Span::styled("[n/Esc] Close", Style::default())));
+    } else if needed.is_empty() {
+        text.push(Line::from(vec![
             Span::styled("  [y] Confirm  ", Style::default().fg(Color::Green)),
             Span::styled("[n/Esc] Cancel", Style::default().fg(Color::Red)),
-        ]),
-        Line::from(""),
-    ];
+        ]));
+    } else {
+        text.push(Line::from(Span::styled(
+            "⚠ Installed packages still depend on this:",
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        )));
+        text.extend(needed);
+        text.push(Line::from(""));
+        text.push(Line::from(vec![
+            Span::styled("  [!] Uninstall anyway (--ignore-dependencies)  ", Style::default().fg(Color::Red)),
+            Span::styled("[n/Esc] Cancel", Style::default()),
+        ]));
+    }
+    text.push(Line::from(""));
+
+    let width = if dependents.is_empty() && denied.is_empty() && !mixed { 44 } else { 72 };
+    let popup = centered_rect(width, text.len() as u16 + 2, area);
+    f.render_widget(Clear, popup);
 
     let block = Block::default()
         .borders(Borders::ALL)
@@ -52,46 +171,29 @@ pub fn render_confirm(f: &mut Frame, area: Rect, action: &BrewAction) {
     f.render_widget(para, popup);
 }
 
-pub fn render_running(f: &mut Frame, area: Rect, action: &BrewAction, output: &[String], done: bool) {
-    let title = format!(
-        "brew {} {}{}",
-        action.verb(),
-        action.name(),
-        if done { " (done)" } else { " (running…)" }
-    );
+/// Keeps as many of `lines` as fit in `room` (but at least one), ending in
+/// an "…and N more" line when some are left out, and takes them from `room`.
+fn capped<'a>(mut lines: Vec<Line<'a>>, room: &mut usize) -> Vec<Line<'a>> {
+    let keep = lines.len().min((*room).max(1));
+    if keep < lines.len() {
+        let more = lines.len() - keep + 1;
+        lines.truncate(keep - 1);
+        lines.push(Line::from(Span::styled(format!("…and {more} more"), Style::default().add_modifier(Modifier::ITALIC))));
+    }
+    *room = room.saturating_sub(lines.len());
+    lines
+}
 
-    let popup = centered_rect(80, 24, area);
+/// A one-line text prompt, e.g. for the name of a tap to add.
+pub fn render_input(f: &mut Frame, area: Rect, prompt: &str, input: &str) {
+    let width = (prompt.chars().count() as u16 + 4).max(60);
+    let popup = centered_rect(width, 3, area);
     f.render_widget(Clear, popup);
 
-    let items: Vec<ListItem> = output
-        .iter()
-        .map(|l| ListItem::new(l.as_str()))
-        .collect();
-
     let block = Block::default()
         .borders(Borders::ALL)
-        .title(title)
-        .style(Style::default().bg(Color::Black));
-
-    let list = List::new(items).block(block);
-
-    // Scroll to bottom: use a temporary ListState pointing at last item.
-    let mut ls = ratatui::widgets::ListState::default();
-    if !output.is_empty() {
-        ls.select(Some(output.len() - 1));
-    }
-    f.render_stateful_widget(list, popup, &mut ls);
-
-    if done {
-        // Show "press any key" hint at bottom of popup
-        let hint_area = Rect {
-            x: popup.x + 1,
-            y: popup.y + popup.height.saturating_sub(2),
-            width: popup.width.saturating_sub(2),
-            height: 1,
-        };
-        let hint = Paragraph::new("Press any key to close")
-            .style(Style::default().fg(Color::Yellow));
-        f.render_widget(hint, hint_area);
-    }
+        .title(prompt.to_string())
+        .style(Style::default().bg(Color::DarkGray));
+    let para = Paragraph::new(Line::from(vec![Span::raw(input.to_string()), Span::raw("█")])).block(block);
+    f.render_widget(para, popup);
 }
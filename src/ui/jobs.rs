@@ -0,0 +1,84 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::app::App;
+use crate::jobs::{Job, JobStatus};
+
+use super::dialog::centered_rect;
+
+pub fn render_jobs(f: &mut Frame, area: Rect, app: &App) {
+    let popup = centered_rect(area.width.saturating_sub(8), area.height.saturating_sub(4), area);
+    f.render_widget(Clear, popup);
+
+    let jobs = app.jobs.jobs();
+    let list_height = (jobs.len() as u16 + 2).clamp(3, 10);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(list_height), Constraint::Min(0)])
+        .split(popup);
+
+    let items: Vec<ListItem> = jobs
+        .iter()
+        .map(|job| {
+            let (marker, color) = status_marker(job);
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{marker} "), Style::default().fg(color)),
+                Span::raw(format!("#{} {}", job.id, job.command_line())),
+            ]))
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Jobs")
+        .style(Style::default().bg(Color::Black));
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED));
+    let mut ls = app.jobs_state.clone();
+    f.render_stateful_widget(list, chunks[0], &mut ls);
+
+    let selected = app.jobs_state.selected().and_then(|i| jobs.get(i));
+    let (title, output): (String, &[String]) = match selected {
+        Some(job) => {
+            let status = match (job.status, job.exit_code) {
+                (JobStatus::Queued, _) => "queued".to_string(),
+                (JobStatus::Running, _) => "running…".to_string(),
+                (JobStatus::Cancelled, _) => "cancelled".to_string(),
+                (_, Some(code)) => format!("exit {code}"),
+                (_, None) => "no exit code".to_string(),
+            };
+            (format!("{} ({status})", job.command_line()), &job.output)
+        }
+        None => ("Output".to_string(), &[]),
+    };
+
+    let items: Vec<ListItem> = output.iter().map(|l| ListItem::new(l.as_str())).collect();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .style(Style::default().bg(Color::Black));
+    let list = List::new(items).block(block);
+
+    // Scroll to bottom: use a temporary ListState pointing at last item.
+    let mut ls = ListState::default();
+    if !output.is_empty() {
+        ls.select(Some(output.len() - 1));
+    }
+    f.render_stateful_widget(list, chunks[1], &mut ls);
+}
+
+fn status_marker(job: &Job) -> (&'static str, Color) {
+    match job.status {
+        JobStatus::Queued => ("…", Color::DarkGray),
+        JobStatus::Running => ("⟳", Color::Yellow),
+        JobStatus::Succeeded => ("✓", Color::Green),
+        JobStatus::Failed => ("✗", Color::Red),
+        JobStatus::Cancelled => ("⊘", Color::DarkGray),
+    }
+}
@@ -0,0 +1,56 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::app::App;
+use crate::brew::types::RemoteData;
+
+use super::dialog::centered_rect;
+
+pub fn render_licenses(f: &mut Frame, area: Rect, app: &App) {
+    let popup = centered_rect(area.width.saturating_sub(8), area.height.saturating_sub(4), area);
+    f.render_widget(Clear, popup);
+
+    let groups = app.license_audit();
+    let mut lines = vec![];
+    for group in &groups {
+        let mut header = vec![
+            Span::styled(group.label().to_string(), Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(format!(" ({})", group.packages.len())),
+        ];
+        if group.unknown {
+            header.push(Span::styled("  unknown", Style::default().fg(Color::Red)));
+        } else if group.copyleft {
+            header.push(Span::styled("  copyleft", Style::default().fg(Color::Yellow)));
+        }
+        lines.push(Line::from(header));
+        let names: Vec<&str> = group.packages.iter().map(|p| p.name.as_str()).collect();
+        lines.push(Line::from(Span::styled(
+            format!("  {}", names.join(", ")),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    let formulae: usize = groups.iter().map(|g| g.packages.len()).sum();
+    let copyleft: usize = groups.iter().filter(|g| g.copyleft).map(|g| g.packages.len()).sum();
+    let unknown: usize = groups.iter().filter(|g| g.unknown).map(|g| g.packages.len()).sum();
+    let loading = match app.browse_formulae {
+        RemoteData::Loading => " (loading catalog…)",
+        _ => "",
+    };
+    let title = format!("Licenses: {formulae} formulae, {copyleft} copyleft, {unknown} unknown{loading}");
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .style(Style::default().bg(Color::Black));
+    let para = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((app.licenses_scroll, 0));
+    f.render_widget(para, popup);
+}
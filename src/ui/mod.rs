@@ -1,6 +1,10 @@
+mod brewfile;
 mod debug;
+mod dep_tree;
 mod detail;
 mod dialog;
+mod jobs;
+mod licenses;
 mod package_list;
 mod render;
 mod tabs;
@@ -1,46 +1,199 @@
 use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState},
     Frame,
 };
 
-use crate::app::{App, Mode, Tab};
-use crate::brew::types::RemoteData;
+use crate::app::{App, Mode, Tab, VisibleItem};
+use crate::brew::types::{CatalogSource, PackageKind, RemoteData};
+use crate::manifest::Outcome;
+use crate::search::SearchScope;
+use crate::sort::SortKey;
 
 pub fn render_package_list(f: &mut Frame, area: Rect, app: &App, list_state: &mut ListState) {
-    let items: Vec<ListItem> = app
-        .visible_items()
-        .into_iter()
-        .map(|n| ListItem::new(n))
-        .collect();
+    let visible = app.visible_items();
+    let items: Vec<ListItem> = if app.tab == Tab::Outdated {
+        visible
+            .iter()
+            .zip(app.visible_outdated())
+            .map(|(item, p)| {
+                let installed = p.installed_versions.join(", ");
+                let pad = 24usize.saturating_sub(item.name.chars().count());
+                let mut spans = vec![Span::raw(mark_prefix(app, p.kind, &item.name))];
+                spans.extend(highlighted(item));
+                spans.push(Span::raw(format!(
+                    "{:pad$} {:>12} → {}",
+                    "", installed, p.current_version
+                )));
+                if p.pinned {
+                    spans.push(pin_indicator());
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect()
+    } else if app.tab == Tab::Leaves {
+        visible
+            .iter()
+            .map(|item| {
+                let orphan = app
+                    .installed_formulae
+                    .iter()
+                    .any(|f| f.name == item.name && f.is_dependency_install());
+                let mut spans = vec![Span::raw(mark_prefix(app, app.tab_kind(), &item.name))];
+                spans.extend(highlighted(item));
+                spans.push(if orphan {
+                    Span::styled("  orphan", Style::default().fg(Color::Yellow))
+                } else {
+                    Span::styled("  leaf", Style::default().fg(Color::DarkGray))
+                });
+                if app.is_formula_pinned(&item.name) {
+                    spans.push(pin_indicator());
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect()
+    } else if let (Tab::Taps, RemoteData::Loaded(taps)) = (app.tab, &app.taps) {
+        visible
+            .iter()
+            .map(|item| {
+                let mut spans = vec![Span::raw(mark_prefix(app, app.tab_kind(), &item.name))];
+                spans.extend(highlighted(item));
+                if let Some(tap) = taps.iter().find(|t| t.name == item.name) {
+                    spans.push(Span::styled(
+                        format!("  {} formulae, {} casks", tap.formula_names.len(), tap.cask_tokens.len()),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect()
+    } else if let (Tab::Services, RemoteData::Loaded(services)) = (app.tab, &app.services) {
+        visible
+            .iter()
+            .map(|item| {
+                let mut spans = vec![Span::raw(mark_prefix(app, app.tab_kind(), &item.name))];
+                spans.extend(highlighted(item));
+                if let Some(service) = services.iter().find(|s| s.name == item.name) {
+                    let pad = 24usize.saturating_sub(item.name.chars().count());
+                    spans.push(Span::raw(format!("{:pad$} ", "")));
+                    spans.push(Span::styled(service.status.clone(), service_status_style(&service.status)));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect()
+    } else if app.tab == Tab::Compliance {
+        let checks = &app.compliance;
+        visible
+            .iter()
+            .map(|item| {
+                let mut spans = vec![Span::raw(mark_prefix(app, app.tab_kind(), &item.name))];
+                if let Some(check) = checks.iter().find(|c| c.label == item.name) {
+                    spans.push(outcome_span(check.outcome));
+                }
+                spans.extend(highlighted(item));
+                ListItem::new(Line::from(spans))
+            })
+            .collect()
+    } else {
+        visible
+            .iter()
+            .map(|item| {
+                let mut spans = vec![Span::raw(mark_prefix(app, app.tab_kind(), &item.name))];
+                spans.extend(highlighted(item));
+                if app.tab == Tab::InstalledFormulae && app.is_formula_pinned(&item.name) {
+                    spans.push(pin_indicator());
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect()
+    };
 
     let title = match app.tab {
         Tab::InstalledFormulae => "Installed Formulae",
         Tab::InstalledCasks => "Installed Casks",
-        Tab::BrowseFormulae => match &app.browse_formulae {
-            RemoteData::Loading => "Browse Formulae (loading…)",
-            RemoteData::Failed(_) => "Browse Formulae (error)",
+        Tab::Outdated => match &app.outdated {
+            RemoteData::Loading => "Outdated (checking…)",
+            RemoteData::Failed(_) => "Outdated (error)",
+            _ => "Outdated",
+        },
+        Tab::Leaves => "Leaves & Orphans",
+        Tab::BrowseFormulae => match (&app.browse_formulae, app.formulae_source) {
+            (RemoteData::Loading, _) => "Browse Formulae (loading…)",
+            (RemoteData::Failed(_), _) => "Browse Formulae (error)",
+            _ if app.formulae_error.is_some() => "Browse Formulae (incomplete)",
+            (_, Some(CatalogSource::Stale)) => "Browse Formulae (offline, cached)",
+            (_, Some(CatalogSource::Cached)) => "Browse Formulae (cached, refreshing…)",
             _ => "Browse Formulae",
         },
-        Tab::BrowseCasks => match &app.browse_casks {
-            RemoteData::Loading => "Browse Casks (loading…)",
-            RemoteData::Failed(_) => "Browse Casks (error)",
+        Tab::BrowseCasks => match (&app.browse_casks, app.casks_source) {
+            (RemoteData::Loading, _) => "Browse Casks (loading…)",
+            (RemoteData::Failed(_), _) => "Browse Casks (error)",
+            _ if app.casks_error.is_some() => "Browse Casks (incomplete)",
+            (_, Some(CatalogSource::Stale)) => "Browse Casks (offline, cached)",
+            (_, Some(CatalogSource::Cached)) => "Browse Casks (cached, refreshing…)",
             _ => "Browse Casks",
         },
+        Tab::Taps => match &app.taps {
+            RemoteData::Loading => "Taps (loading…)",
+            RemoteData::Failed(_) => "Taps (error)",
+            _ => "Taps",
+        },
+        Tab::Services => match &app.services {
+            RemoteData::Loading => "Services (loading…)",
+            RemoteData::Failed(_) => "Services (error)",
+            _ => "Services",
+        },
+        Tab::Compliance => match &app.manifest {
+            Some(_) => "Compliance",
+            None => "Compliance (no manifest)",
+        },
+    };
+
+    let scope = match app.search_scope {
+        SearchScope::Name => "",
+        SearchScope::FullText => "full text ",
+    };
+    let search_hint = if matches!(app.mode, Mode::Search) || !app.search.is_empty() {
+        format!(" [{scope}/{}]", app.search)
+    } else if app.search_scope == SearchScope::FullText {
+        " [full text]".to_string()
+    } else {
+        String::new()
     };
 
-    let search_hint = if matches!(app.mode, Mode::Search) {
-        format!(" [/{}]", app.search)
-    } else if !app.search.is_empty() {
-        format!(" [/{}]", app.search)
+    let sort_hint = if app.sort_key == SortKey::Default || !app.tab.is_sortable() {
+        String::new()
     } else {
+        let arrow = if app.sort_descending { "↓" } else { "↑" };
+        let data = match app.sort_key {
+            SortKey::Size => &app.disk_usage,
+            SortKey::Popularity => &app.popularity,
+            _ => &RemoteData::NotLoaded,
+        };
+        let status = match data {
+            RemoteData::Loading => " loading…",
+            RemoteData::Failed(_) => " unavailable",
+            _ => "",
+        };
+        format!(" [sort: {} {arrow}{status}]", app.sort_key.label())
+    };
+
+    let error_hint = match app.parsed_query() {
+        Err(e) => format!(" (query error: {e})"),
+        Ok(_) => String::new(),
+    };
+
+    let marked_hint = if app.marked.is_empty() {
         String::new()
+    } else {
+        format!(" ({} marked)", app.marked.len())
     };
 
     let block = Block::default()
         .borders(Borders::ALL)
-        .title(format!("{title}{search_hint}"));
+        .title(format!("{title}{search_hint}{sort_hint}{error_hint}{marked_hint}"));
 
     let list = List::new(items)
         .block(block)
@@ -54,3 +207,55 @@ pub fn render_package_list(f: &mut Frame, area: Rect, app: &App, list_state: &mu
 
     f.render_stateful_widget(list, area, list_state);
 }
+
+/// Splits a name into spans, emphasising the characters matched by search.
+fn highlighted(item: &VisibleItem) -> Vec<Span<'static>> {
+    let matched = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    let mut spans = vec![];
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, ch) in item.name.chars().enumerate() {
+        let is_match = item.positions.contains(&i);
+        if is_match != run_matched && !run.is_empty() {
+            let text = std::mem::take(&mut run);
+            spans.push(if run_matched { Span::styled(text, matched) } else { Span::raw(text) });
+        }
+        run_matched = is_match;
+        run.push(ch);
+    }
+    if !run.is_empty() {
+        spans.push(if run_matched { Span::styled(run, matched) } else { Span::raw(run) });
+    }
+    spans
+}
+
+pub fn outcome_span(outcome: Outcome) -> Span<'static> {
+    match outcome {
+        Outcome::Pass => Span::styled("✓ ", Style::default().fg(Color::Green)),
+        Outcome::Fail => Span::styled("✗ ", Style::default().fg(Color::Red)),
+        Outcome::Skipped => Span::styled("– ", Style::default().fg(Color::DarkGray)),
+    }
+}
+
+pub fn service_status_style(status: &str) -> Style {
+    match status {
+        "started" => Style::default().fg(Color::Green),
+        "error" => Style::default().fg(Color::Red),
+        "stopped" | "none" => Style::default().fg(Color::DarkGray),
+        _ => Style::default().fg(Color::Yellow),
+    }
+}
+
+fn pin_indicator() -> Span<'static> {
+    Span::styled("  📌 pinned", Style::default().fg(Color::Magenta))
+}
+
+fn mark_prefix(app: &App, kind: PackageKind, name: &str) -> &'static str {
+    if app.is_marked(kind, name) {
+        "● "
+    } else {
+        "  "
+    }
+}
@@ -8,9 +8,13 @@ use ratatui::{
 
 use crate::app::{App, Mode};
 
+use super::brewfile::render_brewfile_plan;
 use super::debug::render_debug_panel;
+use super::dep_tree::render_dep_tree;
 use super::detail::render_detail;
-use super::dialog::{render_confirm, render_running};
+use super::dialog::{render_confirm, render_input};
+use super::jobs::render_jobs;
+use super::licenses::render_licenses;
 use super::package_list::render_package_list;
 use super::tabs::render_tabs;
 
@@ -71,10 +75,14 @@ pub fn render(f: &mut Frame, app: &App) {
 
     // Overlays
     match &app.mode {
-        Mode::Confirm { action } => render_confirm(f, size, action),
-        Mode::Running { action, output, done } => {
-            render_running(f, size, action, output, *done)
+        Mode::Confirm { actions, dependents, skipped, denied } => {
+            render_confirm(f, size, actions, dependents, skipped, denied)
         }
+        Mode::Jobs => render_jobs(f, size, app),
+        Mode::DepTree => render_dep_tree(f, size, app),
+        Mode::Brewfile => render_brewfile_plan(f, size, app),
+        Mode::Licenses => render_licenses(f, size, app),
+        Mode::Input(purpose) => render_input(f, size, purpose.prompt(), &app.input),
         _ => {}
     }
 }
@@ -83,16 +91,52 @@ fn help_text(app: &App) -> Vec<Span<'static>> {
     match &app.mode {
         Mode::Search => vec![
             Span::raw(" Type to filter  "),
+            Span::raw("Ctrl-F name/full text  "),
             Span::raw("Enter accept  "),
             Span::raw("Esc cancel"),
         ],
+        Mode::Input(_) => {
+            let mut spans = vec![
+                Span::raw(" Enter submit  "),
+                Span::raw("Esc cancel"),
+            ];
+            if let Some(notice) = &app.notice {
+                spans.push(Span::styled(format!("  {notice}"), Style::default().fg(Color::Red)));
+            }
+            spans
+        }
+        Mode::Confirm { dependents, .. } if !dependents.is_empty() => vec![
+            Span::styled(" ! uninstall anyway  ", Style::default().fg(Color::Red)),
+            Span::raw("n/Esc cancel"),
+        ],
+        Mode::Confirm { actions, .. } if actions.is_empty() => vec![Span::raw(" n/Esc close")],
         Mode::Confirm { .. } => vec![
             Span::raw(" y confirm  "),
             Span::raw("n/Esc cancel"),
         ],
-        Mode::Running { .. } => vec![
-            Span::raw(" Waiting for brew…  "),
-            Span::raw("Esc/Enter close when done"),
+        Mode::Jobs => vec![
+            Span::raw(" ↑↓/jk select job  "),
+            Span::styled("x cancel  ", Style::default().fg(Color::Red)),
+            Span::raw("c clear finished  "),
+            Span::raw("Esc close"),
+        ],
+        Mode::DepTree => vec![
+            Span::raw(" ↑↓/jk move  "),
+            Span::raw("Space/→ expand  "),
+            Span::raw("← collapse  "),
+            Span::raw("Enter show package  "),
+            Span::raw("Esc close"),
+        ],
+        Mode::Brewfile => vec![
+            Span::raw(" ↑↓/jk scroll  "),
+            Span::styled("i install missing  ", Style::default().fg(Color::Green)),
+            Span::styled("s sync (install and remove)  ", Style::default().fg(Color::Red)),
+            Span::raw("Esc close"),
+        ],
+        Mode::Licenses => vec![
+            Span::raw(" ↑↓/jk scroll  "),
+            Span::raw("e export report  "),
+            Span::raw("Esc close"),
         ],
         Mode::Normal => {
             use crate::app::Tab;
@@ -102,19 +146,75 @@ fn help_text(app: &App) -> Vec<Span<'static>> {
                 Span::raw("↑↓/jk navigate  "),
                 Span::raw("/ search  "),
                 Span::raw("r refresh  "),
+                Span::raw("Space mark  "),
+                Span::raw("* invert  "),
             ];
+            if app.tab.is_sortable() {
+                spans.push(Span::raw("o/O sort  "));
+            }
             match app.tab {
-                Tab::BrowseFormulae | Tab::BrowseCasks => {
+                Tab::BrowseFormulae => {
+                    spans.push(Span::raw("d deps  "));
+                    spans.push(Span::styled("i install", Style::default().fg(Color::Green)));
+                }
+                Tab::BrowseCasks => {
                     spans.push(Span::styled("i install", Style::default().fg(Color::Green)));
                 }
-                Tab::InstalledFormulae | Tab::InstalledCasks => {
+                Tab::InstalledFormulae => {
+                    spans.push(Span::raw("d deps  "));
+                    spans.push(Span::raw("p/P pin/unpin  "));
                     spans.push(Span::styled("u uninstall  ", Style::default().fg(Color::Red)));
                     spans.push(Span::styled("U upgrade", Style::default().fg(Color::Yellow)));
                 }
+                Tab::InstalledCasks => {
+                    spans.push(Span::styled("u uninstall  ", Style::default().fg(Color::Red)));
+                    spans.push(Span::styled("U upgrade", Style::default().fg(Color::Yellow)));
+                }
+                Tab::Outdated => {
+                    spans.push(Span::styled("U upgrade", Style::default().fg(Color::Yellow)));
+                }
+                Tab::Compliance => {
+                    spans.push(Span::styled("f fix  ", Style::default().fg(Color::Green)));
+                    spans.push(Span::styled("F fix all failures", Style::default().fg(Color::Yellow)));
+                }
+                Tab::Services => {
+                    spans.push(Span::styled("s start  ", Style::default().fg(Color::Green)));
+                    spans.push(Span::styled("x stop  ", Style::default().fg(Color::Red)));
+                    spans.push(Span::raw("R restart  "));
+                    spans.push(Span::raw("n run"));
+                }
+                Tab::Taps => {
+                    spans.push(Span::raw("Enter browse  "));
+                    spans.push(Span::styled("t tap  ", Style::default().fg(Color::Green)));
+                    spans.push(Span::styled("u untap", Style::default().fg(Color::Red)));
+                }
+                Tab::Leaves => {
+                    spans.push(Span::raw("d deps  "));
+                    spans.push(Span::raw("p/P pin/unpin  "));
+                    spans.push(Span::styled("u uninstall  ", Style::default().fg(Color::Red)));
+                    spans.push(Span::styled("U upgrade  ", Style::default().fg(Color::Yellow)));
+                    spans.push(Span::styled("A autoremove", Style::default().fg(Color::Red)));
+                }
+            }
+            spans.push(Span::raw("  J jobs  "));
+            spans.push(Span::raw("B Brewfile  "));
+            spans.push(Span::raw("E export  "));
+            spans.push(Span::raw("L licenses"));
+            if let Some(job) = app.jobs.running() {
+                let queued = app.jobs.queued_count();
+                let status = if queued > 0 {
+                    format!(" [⟳ #{} running, {queued} queued]", job.id)
+                } else {
+                    format!(" [⟳ #{} running]", job.id)
+                };
+                spans.push(Span::styled(status, Style::default().fg(Color::Yellow)));
             }
             if app.debug_mode {
                 spans.push(Span::raw("  ?  debug"));
             }
+            if let Some(notice) = &app.notice {
+                spans.push(Span::styled(format!("  {notice}"), Style::default().fg(Color::Cyan)));
+            }
             spans
         }
     }
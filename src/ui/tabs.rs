@@ -9,17 +9,27 @@ use ratatui::{
 use crate::app::Tab;
 
 pub fn render_tabs(f: &mut Frame, area: Rect, tab: Tab) {
-    let titles = vec![
+    let titles = [
         "Installed: Formulae",
         "Installed: Casks",
+        "Outdated",
+        "Leaves",
         "Browse: Formulae",
         "Browse: Casks",
+        "Taps",
+        "Services",
+        "Compliance",
     ];
     let selected = match tab {
         Tab::InstalledFormulae => 0,
         Tab::InstalledCasks => 1,
-        Tab::BrowseFormulae => 2,
-        Tab::BrowseCasks => 3,
+        Tab::Outdated => 2,
+        Tab::Leaves => 3,
+        Tab::BrowseFormulae => 4,
+        Tab::BrowseCasks => 5,
+        Tab::Taps => 6,
+        Tab::Services => 7,
+        Tab::Compliance => 8,
     };
     let tabs = Tabs::new(titles.iter().map(|t| Line::from(Span::raw(*t))).collect::<Vec<_>>())
         .block(Block::default().borders(Borders::ALL).title("taphouse 🍺"))